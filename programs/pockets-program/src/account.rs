@@ -1,5 +1,23 @@
 use crate::constant::*;
+use crate::error::*;
+use crate::event::*;
+use crate::math::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::Discriminator;
+
+#[account]
+pub struct ProgramConfig {
+    pub authority: Pubkey, // Server key allowed to call admin instructions
+    pub pending_authority: Option<Pubkey>, // Set by propose_authority, cleared on accept
+    pub bump: u8,
+}
+
+impl MaxSize for ProgramConfig {
+    fn get_max_size() -> usize {
+        32 + 33 + 1
+    }
+}
 
 #[account]
 pub struct Faction {
@@ -7,12 +25,82 @@ pub struct Faction {
     pub max_voting_power: u64,
     pub threshold_to_pass: u64,
     pub unallocated_voting_power: u64,
+    pub governance_mint: Option<Pubkey>, // Deposits of this token are counted as voting power
+    pub voting_mode: VotingMode,
+    pub conviction_half_life_seconds: i64, // 0 disables conviction proposals
+    pub conviction_spending_limit_bps: u64, // Most of the treasury a single conviction proposal can ask for
+    pub conviction_weight_bps: u64,
+    pub threshold_mode: ThresholdMode,
+    pub quorum_bps: u64,   // Percentage mode, share of supply that has to vote
+    pub approval_bps: u64, // Percentage mode, share of cast votes the winner needs
+    pub council: Option<Pubkey>, // Can veto proposals before they're executed
+    pub proposal_min_voting_power: u64, // Citizen proposals pledge this much voting power
+    pub proposal_deposit: u64, // and escrow this many lamports
+    pub citizen_proposals_enabled: bool, // Off until set_proposal_requirements turns it on
+    pub liquid_delegation_depth: u8, // How many times delegated power can be forwarded, 0 is off
+    pub member_count: u64,
+    pub open_proposal_count: u64, // DRAFT or VOTING, the faction can't be deleted while any are
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdMode {
+    Absolute, // Winner needs threshold_to_pass votes
+    Percentage,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VotingMode {
+    Linear,
+    Quadratic, // A vote spending N voting power counts as floor(sqrt(N))
+}
+
+impl Faction {
+    // Sends the citizen's granted power back to unallocated and drops them from the faction,
+    // they need to have cleared everything leave_faction asks for. Returns the power sent back
+    pub fn remove_member(&mut self, citizen: &mut Citizen) -> Result<u64> {
+        if citizen.deposited_voting_power != 0 {
+            return err!(PocketErrors::GovernanceTokensDeposited);
+        }
+        if citizen.locked_voting_power != 0 {
+            return err!(PocketErrors::LockupActive);
+        }
+        if citizen.max_pledged_voting_power != 0
+            || citizen.delegated_voting_power != 0
+            || citizen.topic_delegated_voting_power.iter().any(|p| *p != 0)
+        {
+            return err!(PocketErrors::CitizenHasOutstandingVotes);
+        }
+
+        let returned_voting_power = citizen.granted_voting_power;
+        self.unallocated_voting_power =
+            safe_add(self.unallocated_voting_power, returned_voting_power)?;
+        self.member_count = safe_sub(self.member_count, 1)?;
+
+        citizen.faction = None;
+        citizen.delegated_voting_power = 0;
+        citizen.granted_voting_power = 0;
+        citizen.total_voting_power = 0;
+        Ok(returned_voting_power)
+    }
+
+    // Call after every proposal status change, counts it off once it's no longer open
+    pub fn track_proposal(
+        &mut self,
+        old_status: &ProposalStatus,
+        new_status: &ProposalStatus,
+    ) -> Result<()> {
+        if old_status.is_open() && !new_status.is_open() {
+            self.open_proposal_count = safe_sub(self.open_proposal_count, 1)?;
+        }
+        Ok(())
+    }
 }
 
 impl MaxSize for Faction {
     fn get_max_size() -> usize {
         // String is 4 + len, nanoid() churns out 21 character ids
-        return (4 + 21) + 8 + 8 + 8;
+        (4 + 21) + 8 + 8 + 8 + 33 + 1 + 8 + 8 + 8 + 1 + 8 + 8 + 33 + 8 + 8 + 1 + 1 + 8 + 8 + 1
     }
 }
 
@@ -24,11 +112,287 @@ pub struct Citizen {
     pub granted_voting_power: u64,
     pub total_voting_power: u64,
     pub max_pledged_voting_power: u64,
+    pub deposited_voting_power: u64, // Part of granted_voting_power backed by governance tokens
+    pub locked_voting_power: u64,    // Part of granted_voting_power held in a Lockup
+    // Power delegated for a single ProposalCategory, kept out of total_voting_power
+    pub topic_delegated_voting_power: [u64; TOPIC_CATEGORIES],
+    pub topic_pledged_voting_power: [u64; TOPIC_CATEGORIES],
+    pub vote_count: u64, // Live ProposalVote accounts, unwinding delegated power has to cover all of them
+    pub topic_vote_count: [u64; TOPIC_CATEGORIES], // Of those, votes on proposals in each topic category
+    pub bump: u8,
+}
+
+impl Citizen {
+    // Voting power that isn't pledged to proposals or delegated away
+    pub fn free_voting_power(&self) -> Result<u64> {
+        self.total_voting_power
+            .checked_sub(self.max_pledged_voting_power)
+            .ok_or(error!(PocketErrors::PledgeExceedsTotal))
+    }
+
+    // Granted voting power that isn't pledged, delegated away or locked up
+    pub fn unlocked_voting_power(&self) -> Result<u64> {
+        let unlocked = safe_sub(self.granted_voting_power, self.locked_voting_power)?;
+        Ok(unlocked.min(self.free_voting_power()?))
+    }
+
+    // Unlocked power that can move to another citizen, the deposited part stays with the
+    // tokens in this citizen's vault so withdraw_governance_tokens can always release them
+    pub fn transferable_voting_power(&self) -> Result<u64> {
+        let transferable = self
+            .granted_voting_power
+            .saturating_sub(self.locked_voting_power)
+            .saturating_sub(self.deposited_voting_power);
+        Ok(transferable.min(self.free_voting_power()?))
+    }
+
+    // Topic power delegated for category that isn't pledged to proposals, always 0 for General
+    pub fn free_topic_voting_power(&self, category: ProposalCategory) -> Result<u64> {
+        match category.topic_index() {
+            Some(i) => self.topic_delegated_voting_power[i]
+                .checked_sub(self.topic_pledged_voting_power[i])
+                .ok_or(error!(PocketErrors::PledgeExceedsTotal)),
+            None => Ok(0),
+        }
+    }
+
+    // Call when one of the citizen's ProposalVote accounts is created
+    pub fn open_vote(&mut self, category: ProposalCategory) -> Result<()> {
+        self.vote_count = safe_add(self.vote_count, 1)?;
+        if let Some(i) = category.topic_index() {
+            self.topic_vote_count[i] = safe_add(self.topic_vote_count[i], 1)?;
+        }
+        Ok(())
+    }
+
+    // Call when one of the citizen's ProposalVote accounts is closed
+    pub fn close_vote(&mut self, category: ProposalCategory) -> Result<()> {
+        self.vote_count = safe_sub(self.vote_count, 1)?;
+        if let Some(i) = category.topic_index() {
+            self.topic_vote_count[i] = safe_sub(self.topic_vote_count[i], 1)?;
+        }
+        Ok(())
+    }
+
+    // Votes that power delegated in category could be pledged in
+    pub fn votes_in(&self, category: ProposalCategory) -> u64 {
+        match category.topic_index() {
+            Some(i) => self.topic_vote_count[i],
+            None => self.vote_count,
+        }
+    }
+
+    // Delegated power in category that could be handed back right now
+    pub fn free_delegated_voting_power(&self, category: ProposalCategory) -> Result<u64> {
+        match category.topic_index() {
+            Some(_) => self.free_topic_voting_power(category),
+            None => Ok(self.delegated_voting_power.min(self.free_voting_power()?)),
+        }
+    }
+
+    pub fn add_delegated_voting_power(
+        &mut self,
+        category: ProposalCategory,
+        amount: u64,
+    ) -> Result<()> {
+        match category.topic_index() {
+            Some(i) => {
+                self.topic_delegated_voting_power[i] =
+                    safe_add(self.topic_delegated_voting_power[i], amount)?;
+            }
+            None => {
+                self.delegated_voting_power = safe_add(self.delegated_voting_power, amount)?;
+                self.total_voting_power = safe_add(self.total_voting_power, amount)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_delegated_voting_power(
+        &mut self,
+        category: ProposalCategory,
+        amount: u64,
+    ) -> Result<()> {
+        match category.topic_index() {
+            Some(i) => {
+                self.topic_delegated_voting_power[i] =
+                    safe_sub(self.topic_delegated_voting_power[i], amount)?;
+            }
+            None => {
+                self.delegated_voting_power = safe_sub(self.delegated_voting_power, amount)?;
+                self.total_voting_power = safe_sub(self.total_voting_power, amount)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Pledges amount to a proposal in category, power delegated for that category goes first.
+    // Returns the part that came out of topic power
+    pub fn pledge_voting_power(&mut self, category: ProposalCategory, amount: u64) -> Result<u64> {
+        let topic_amt = amount.min(self.free_topic_voting_power(category)?);
+        let general_amt = amount - topic_amt;
+        if self.free_voting_power()? < general_amt {
+            return err!(PocketErrors::CitizenLacksVotingPower);
+        }
+        self.max_pledged_voting_power = safe_add(self.max_pledged_voting_power, general_amt)?;
+        if let Some(i) = category.topic_index() {
+            self.topic_pledged_voting_power[i] =
+                safe_add(self.topic_pledged_voting_power[i], topic_amt)?;
+        }
+        Ok(topic_amt)
+    }
+
+    pub fn release_voting_power(
+        &mut self,
+        category: ProposalCategory,
+        general_amt: u64,
+        topic_amt: u64,
+    ) -> Result<()> {
+        self.max_pledged_voting_power = safe_sub(self.max_pledged_voting_power, general_amt)?;
+        if let Some(i) = category.topic_index() {
+            self.topic_pledged_voting_power[i] =
+                safe_sub(self.topic_pledged_voting_power[i], topic_amt)?;
+        }
+        Ok(())
+    }
 }
 
 impl MaxSize for Citizen {
     fn get_max_size() -> usize {
-        return 32 + 8 + 8 + 33 + 8 + 8;
+        32 + 8
+            + 8
+            + 33
+            + 8
+            + 8
+            + 8
+            + 8
+            + (TOPIC_CATEGORIES * 8) * 2
+            + 8
+            + (TOPIC_CATEGORIES * 8)
+            + 1
+    }
+}
+
+// Ring buffer of the citizen's total_voting_power over time, so proposals can look up
+// how much power a citizen had at their snapshot slot
+#[account]
+pub struct VotingPowerCheckpoints {
+    pub citizen: Pubkey,
+    pub head: u8, // Next slot to overwrite once the buffer is full
+    pub checkpoints: Vec<Checkpoint>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Checkpoint {
+    pub slot: u64,
+    pub power: u64,
+}
+
+impl MaxSize for VotingPowerCheckpoints {
+    fn get_max_size() -> usize {
+        32 + 1 + (4 + MAX_CHECKPOINTS * (8 + 8)) + 1
+    }
+}
+
+impl VotingPowerCheckpoints {
+    // Call after every change to the citizen's total_voting_power
+    pub fn record(&mut self, power: u64) -> Result<()> {
+        self.record_at(Clock::get()?.slot, power)
+    }
+
+    // Checkpoints are never changed once their slot has passed. When the buffer is full the
+    // oldest is only overwritten if it's older than MIN_CHECKPOINT_HISTORY_SLOTS, otherwise
+    // the change is refused so an open proposal's snapshot can't be lost or rewritten
+    pub fn record_at(&mut self, slot: u64, power: u64) -> Result<()> {
+        let checkpoint = Checkpoint { slot, power };
+
+        if let Some(last) = self.checkpoints.iter_mut().max_by_key(|c| c.slot) {
+            // Nothing changed, don't spend a spot in the buffer on it
+            if last.power == power {
+                return Ok(());
+            }
+            if last.slot == slot {
+                last.power = power;
+                return Ok(());
+            }
+        }
+
+        if self.checkpoints.len() < MAX_CHECKPOINTS {
+            self.checkpoints.push(checkpoint);
+        } else if slot.saturating_sub(self.checkpoints[self.head as usize].slot)
+            < MIN_CHECKPOINT_HISTORY_SLOTS
+        {
+            return err!(PocketErrors::CheckpointHistoryFull);
+        } else {
+            self.checkpoints[self.head as usize] = checkpoint;
+            self.head = ((self.head as usize + 1) % MAX_CHECKPOINTS) as u8;
+        }
+        Ok(())
+    }
+
+    pub fn latest_slot(&self) -> Option<u64> {
+        self.checkpoints.iter().map(|c| c.slot).max()
+    }
+
+    // Voting power as of the given slot
+    pub fn power_at(&self, slot: u64) -> Result<u64> {
+        match self
+            .checkpoints
+            .iter()
+            .filter(|c| c.slot <= slot)
+            .max_by_key(|c| c.slot)
+        {
+            Some(checkpoint) => Ok(checkpoint.power),
+            // History starts when the citizen is created with 0 power, unless it's been overwritten
+            None if self.checkpoints.len() < MAX_CHECKPOINTS => Ok(0),
+            None => err!(PocketErrors::SnapshotUnavailable),
+        }
+    }
+}
+
+// Granted voting power locked until end_ts in exchange for a bonus that decays
+// linearly to nothing at unlock. The bonus is counted in the citizen's total_voting_power
+#[account]
+pub struct Lockup {
+    pub citizen: Pubkey,
+    pub amount: u64,
+    pub bonus_voting_power: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub bump: u8,
+}
+
+impl MaxSize for Lockup {
+    fn get_max_size() -> usize {
+        32 + 8 + 8 + 8 + 8 + 1
+    }
+}
+
+impl Lockup {
+    pub fn bonus_at(&self, now: i64) -> Result<u64> {
+        if now >= self.end_ts {
+            return Ok(0);
+        }
+        let remaining = (self.end_ts - now).min(MAX_LOCKUP_SECONDS) as u64;
+        let bonus_bps = mul_div(MAX_LOCKUP_BONUS_BPS, remaining, MAX_LOCKUP_SECONDS as u64)
+            .ok_or(error!(PocketErrors::Overflow))?;
+        mul_div(self.amount, bonus_bps, BPS_DENOMINATOR).ok_or(error!(PocketErrors::Overflow))
+    }
+
+    // Moves the citizen's total_voting_power by the change in bonus
+    pub fn apply_bonus(&mut self, citizen: &mut Citizen, bonus: u64) -> Result<()> {
+        if bonus > self.bonus_voting_power {
+            citizen.total_voting_power =
+                safe_add(citizen.total_voting_power, bonus - self.bonus_voting_power)?;
+        } else {
+            citizen.total_voting_power =
+                safe_sub(citizen.total_voting_power, self.bonus_voting_power - bonus)?;
+            // Bonus that's pledged can't be taken away
+            citizen.free_voting_power()?;
+        }
+        self.bonus_voting_power = bonus;
+        Ok(())
     }
 }
 
@@ -36,20 +400,478 @@ impl MaxSize for Citizen {
 pub struct Proposal {
     pub id: String, // nanoid() in Pockets DB
     pub faction: Pubkey,
-    pub vote_amt: u64,
+    pub proposer: Pubkey, // Citizen that can cancel the proposal
+    pub vote_amt: u64,    // Total votes across all options
     pub status: ProposalStatus,
+    pub options: Vec<String>,
+    pub tallies: Vec<u64>, // One per option, same order as options
+    pub winning_option: Option<u8>,
+    pub start_ts: i64, // Votes are accepted from start_ts to end_ts (unix seconds)
+    pub end_ts: i64,
+    pub finalized_at: Option<i64>,
+    pub snapshot_slot: u64, // Votes are capped at each citizen's voting power as of this slot
+    pub voting_mode: VotingMode, // Copied from the faction so tallies stay consistent
+    pub proposal_type: ProposalType,
+    pub requested_amount: u64, // Conviction only, lamports asked of the treasury
+    pub conviction: u64,
+    pub conviction_updated_at: i64,
+    pub conviction_half_life_seconds: i64, // Copied from the faction
+    pub supply_snapshot: u64,              // Faction max_voting_power at creation, for quorum
+    pub proposer_pledge: u64,              // Voting power the proposer pledged to create it
+    pub deposit: u64,                      // Lamports escrowed in this account by the proposer
+    pub metadata: ProposalMetadata,
+    pub category: ProposalCategory, // Only delegations for this category (or General) count
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalCategory {
+    General, // Delegating in General counts on every proposal
+    Resource,
+    Treasury,
+    Membership,
+}
+
+impl ProposalCategory {
+    // Index into the citizen's topic arrays, General power lives in total_voting_power
+    pub fn topic_index(&self) -> Option<usize> {
+        match self {
+            ProposalCategory::General => None,
+            ProposalCategory::Resource => Some(0),
+            ProposalCategory::Treasury => Some(1),
+            ProposalCategory::Membership => Some(2),
+        }
+    }
+}
+
+// Lets UIs render a proposal without the Pockets DB, content_hash is the sha256 of the body at uri
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalMetadata {
+    pub title: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+}
+
+impl ProposalMetadata {
+    pub fn validate(&self) -> Result<()> {
+        if self.title.is_empty()
+            || self.title.len() > MAX_PROPOSAL_TITLE
+            || self.uri.len() > MAX_PROPOSAL_URI
+        {
+            return err!(PocketErrors::InvalidProposalMetadata);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalType {
+    Standard,
+    Conviction, // Support for OPTION_FOR builds up over time, passes once it crosses the threshold
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum ProposalStatus {
+    DRAFT, // Instructions can still be attached, not open for votes
     VOTING,
     PASSED,
-    CLOSED,
+    FAILED,
+    CANCELLED,
+    VETOED,
+    EXECUTED,
+}
+
+impl ProposalStatus {
+    pub fn is_open(&self) -> bool {
+        matches!(self, ProposalStatus::DRAFT | ProposalStatus::VOTING)
+    }
+
+    pub fn can_transition_to(&self, next: &ProposalStatus) -> bool {
+        matches!(
+            (self, next),
+            (ProposalStatus::DRAFT, ProposalStatus::VOTING)
+                | (ProposalStatus::DRAFT, ProposalStatus::CANCELLED)
+                | (ProposalStatus::VOTING, ProposalStatus::PASSED)
+                | (ProposalStatus::VOTING, ProposalStatus::FAILED)
+                | (ProposalStatus::VOTING, ProposalStatus::CANCELLED)
+                | (ProposalStatus::VOTING, ProposalStatus::VETOED)
+                | (ProposalStatus::PASSED, ProposalStatus::VETOED)
+                | (ProposalStatus::PASSED, ProposalStatus::EXECUTED)
+        )
+    }
 }
 
 impl MaxSize for Proposal {
     fn get_max_size() -> usize {
-        return (4 + 21) + 8 + (1 + 4) + 32;
+        (4 + 21)
+            + 32
+            + 32
+            + 8
+            + 1
+            + (4 + MAX_PROPOSAL_OPTIONS * (4 + LONGEST_OPTION_LABEL))
+            + (4 + MAX_PROPOSAL_OPTIONS * 8)
+            + (1 + 1)
+            + 8
+            + 8
+            + (1 + 8)
+            + 8
+            + 1
+            + 1
+            + 8
+            + 8
+            + 8
+            + 8
+            + 8
+            + 8
+            + 8
+            + ((4 + MAX_PROPOSAL_TITLE) + (4 + MAX_PROPOSAL_URI) + 32)
+            + 1
+            + 1
+    }
+}
+
+// Everything that differs between create_proposal, create_citizen_proposal and
+// create_conviction_proposal, see Proposal.init
+pub struct NewProposal {
+    pub id: String,
+    pub proposer: Pubkey,
+    pub options: Option<Vec<String>>, // For/Against/Abstain if None
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub metadata: ProposalMetadata,
+    pub category: ProposalCategory,
+    pub proposal_type: ProposalType,
+    pub requested_amount: u64,
+    pub proposer_pledge: u64,
+    pub deposit: u64,
+}
+
+impl Proposal {
+    // Validates and fills in a freshly created proposal as a DRAFT, counts it as open on the faction
+    pub fn init(
+        &mut self,
+        key: Pubkey,
+        faction: &mut Account<Faction>,
+        new: NewProposal,
+        bump: u8,
+    ) -> Result<()> {
+        let clock = Clock::get().unwrap();
+        if new.end_ts <= new.start_ts || new.end_ts <= clock.unix_timestamp {
+            return err!(PocketErrors::InvalidVotingWindow);
+        }
+        new.metadata.validate()?;
+
+        let options = new.options.unwrap_or(
+            DEFAULT_PROPOSAL_OPTIONS
+                .iter()
+                .map(|o| String::from(*o))
+                .collect(),
+        );
+        if options.len() < 2
+            || options.len() > MAX_PROPOSAL_OPTIONS
+            || options.iter().any(|o| o.len() > LONGEST_OPTION_LABEL)
+        {
+            return err!(PocketErrors::InvalidProposalOptions);
+        }
+
+        self.id = new.id;
+        self.status = ProposalStatus::DRAFT;
+        self.faction = faction.key();
+        self.proposer = new.proposer;
+        self.vote_amt = 0;
+        self.tallies = vec![0; options.len()];
+        self.options = options;
+        self.winning_option = None;
+        self.start_ts = new.start_ts;
+        self.end_ts = new.end_ts;
+        self.finalized_at = None;
+        self.snapshot_slot = clock.slot;
+        self.voting_mode = faction.voting_mode;
+        self.supply_snapshot = faction.max_voting_power;
+        self.proposer_pledge = new.proposer_pledge;
+        self.deposit = new.deposit;
+        self.proposal_type = new.proposal_type;
+        self.requested_amount = new.requested_amount;
+        self.conviction = 0;
+        self.conviction_updated_at = new.start_ts;
+        self.conviction_half_life_seconds = match new.proposal_type {
+            ProposalType::Conviction => faction.conviction_half_life_seconds,
+            ProposalType::Standard => 0,
+        };
+        self.metadata = new.metadata;
+        self.category = new.category;
+        self.bump = bump;
+
+        faction.open_proposal_count = safe_add(faction.open_proposal_count, 1)?;
+
+        emit!(ProposalCreated {
+            proposal: key,
+            faction: faction.key(),
+            id: self.id.clone(),
+            options: self.options.clone(),
+            start_ts: self.start_ts,
+            end_ts: self.end_ts,
+            title: self.metadata.title.clone(),
+            uri: self.metadata.uri.clone(),
+            content_hash: self.metadata.content_hash,
+            category: self.category,
+        });
+        Ok(())
+    }
+
+    // For/Against/Abstain proposals, where only For can pass and Abstain never wins
+    pub fn is_binary(&self) -> bool {
+        self.options.len() == DEFAULT_PROPOSAL_OPTIONS.len()
+            && self
+                .options
+                .iter()
+                .zip(DEFAULT_PROPOSAL_OPTIONS.iter())
+                .all(|(a, b)| a == b)
+    }
+
+    // Option with the highest tally, None if nobody has voted or there's a tie
+    pub fn leading_option(&self) -> Option<u8> {
+        let mut leader: Option<u8> = None;
+        let mut leader_tally: u64 = 0;
+        let mut tied = false;
+        for (i, tally) in self.tallies.iter().enumerate() {
+            if self.is_binary() && i as u8 == OPTION_ABSTAIN {
+                continue;
+            }
+            if *tally > leader_tally {
+                leader = Some(i as u8);
+                leader_tally = *tally;
+                tied = false;
+            } else if *tally == leader_tally && leader.is_some() {
+                tied = true;
+            }
+        }
+        if tied {
+            None
+        } else {
+            leader
+        }
+    }
+
+    // Leading option once it has cleared the faction threshold
+    pub fn resolve(&self, faction: &Faction) -> Option<u8> {
+        let winner = self.leading_option()?;
+        let winner_tally = self.tallies[winner as usize];
+        let passed = match faction.threshold_mode {
+            ThresholdMode::Absolute => winner_tally >= faction.threshold_to_pass,
+            ThresholdMode::Percentage => {
+                // Abstain counts towards quorum but not approval
+                let cast: u64 = self
+                    .tallies
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !(self.is_binary() && *i as u8 == OPTION_ABSTAIN))
+                    .map(|(_, tally)| *tally)
+                    .sum();
+                let approval = mul_div(cast, faction.approval_bps, BPS_DENOMINATOR)?;
+                self.reached_quorum(faction) && winner_tally >= approval
+            }
+        };
+        if passed {
+            Some(winner)
+        } else {
+            None
+        }
+    }
+
+    // Enough voting power turned out, Absolute factions use threshold_to_pass as the bar
+    pub fn reached_quorum(&self, faction: &Faction) -> bool {
+        let quorum = match faction.threshold_mode {
+            ThresholdMode::Absolute => Some(faction.threshold_to_pass),
+            ThresholdMode::Percentage => {
+                mul_div(self.supply_snapshot, faction.quorum_bps, BPS_DENOMINATOR)
+            }
+        };
+        match quorum {
+            Some(quorum) => self.vote_amt >= quorum,
+            None => false,
+        }
+    }
+
+    // Deposit goes to the treasury when the proposal was vetoed or nobody turned up for it
+    pub fn forfeits_deposit(&self, faction: &Faction) -> bool {
+        match self.status {
+            ProposalStatus::VETOED => true,
+            ProposalStatus::FAILED => !self.reached_quorum(faction),
+            _ => false,
+        }
+    }
+
+    // How much a vote of vote_amt counts towards its option's tally
+    pub fn vote_weight(&self, vote_amt: u64) -> u64 {
+        match self.voting_mode {
+            VotingMode::Linear => vote_amt,
+            VotingMode::Quadratic => isqrt(vote_amt as u128) as u64,
+        }
+    }
+
+    pub fn add_vote(&mut self, vote: &ProposalVote) -> Result<()> {
+        self.vote_amt = safe_add(self.vote_amt, vote.vote_amt)?;
+        self.tallies[vote.option as usize] =
+            safe_add(self.tallies[vote.option as usize], vote.weight)?;
+        Ok(())
+    }
+
+    pub fn remove_vote(&mut self, vote: &ProposalVote) -> Result<()> {
+        self.vote_amt = safe_sub(self.vote_amt, vote.vote_amt)?;
+        self.tallies[vote.option as usize] =
+            safe_sub(self.tallies[vote.option as usize], vote.weight)?;
+        Ok(())
+    }
+
+    // Brings conviction up to now, call before the staked amount changes
+    // c(t) = staked + (c0 - staked) * 2^(-dt / half_life)
+    pub fn accrue_conviction(&mut self, now: i64) -> Result<()> {
+        let now = now.min(self.end_ts);
+        if self.proposal_type != ProposalType::Conviction || now <= self.conviction_updated_at {
+            return Ok(());
+        }
+
+        let staked = self.tallies[OPTION_FOR as usize];
+        let factor = decay_factor(
+            (now - self.conviction_updated_at) as u64,
+            self.conviction_half_life_seconds as u64,
+        );
+        self.conviction = if self.conviction >= staked {
+            let decayed = mul_div(self.conviction - staked, factor, DECAY_SCALE)
+                .ok_or(error!(PocketErrors::Overflow))?;
+            safe_add(staked, decayed)?
+        } else {
+            let remaining = mul_div(staked - self.conviction, factor, DECAY_SCALE)
+                .ok_or(error!(PocketErrors::Overflow))?;
+            safe_sub(staked, remaining)?
+        };
+        self.conviction_updated_at = now;
+        Ok(())
+    }
+
+    // weight * supply / (spending_limit - requested / treasury)^2, None if the ask is too big
+    pub fn conviction_threshold(
+        &self,
+        max_voting_power: u64,
+        treasury_lamports: u64,
+        spending_limit_bps: u64,
+        weight_bps: u64,
+    ) -> Option<u64> {
+        if treasury_lamports == 0 {
+            return None;
+        }
+        let share_bps = mul_div(self.requested_amount, BPS_DENOMINATOR, treasury_lamports)?;
+        if share_bps >= spending_limit_bps {
+            return None;
+        }
+        let headroom = (spending_limit_bps - share_bps) as u128;
+        let threshold = (max_voting_power as u128)
+            .checked_mul(weight_bps as u128)?
+            .checked_mul(BPS_DENOMINATOR as u128)?
+            / (headroom * headroom);
+        u64::try_from(threshold).ok()
+    }
+
+    pub fn is_voting_open(&self, now: i64) -> bool {
+        self.status == ProposalStatus::VOTING && now >= self.start_ts && now <= self.end_ts
+    }
+
+    // Records the winner and moves the proposal to PASSED, or FAILED if nothing won
+    // Conviction proposals only pass through update_conviction, so reaching the end here is a fail
+    pub fn finalize(&mut self, faction: &Faction, now: i64) -> Result<ProposalStatus> {
+        self.finalized_at = Some(now);
+        self.winning_option = match self.proposal_type {
+            ProposalType::Standard => self.resolve(faction),
+            ProposalType::Conviction => None,
+        };
+        match self.winning_option {
+            Some(winner) if !self.is_binary() || winner == OPTION_FOR => {
+                self.transition(ProposalStatus::PASSED)
+            }
+            _ => self.transition(ProposalStatus::FAILED),
+        }
+    }
+
+    // Every status change goes through here, returns the old status
+    pub fn transition(&mut self, next: ProposalStatus) -> Result<ProposalStatus> {
+        if !self.status.can_transition_to(&next) {
+            return err!(PocketErrors::IllegalTransition);
+        }
+        let old_status = self.status.clone();
+        self.status = next;
+        Ok(old_status)
+    }
+}
+
+// Instructions run by execute_proposal, signed by the Faction PDA, once the proposal passes
+#[account]
+pub struct ProposalInstruction {
+    pub proposal: Pubkey,
+    pub hold_up_seconds: i64, // Timelock between the proposal passing and execution
+    pub executed: bool,
+    pub instructions: Vec<InstructionData>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InstructionData {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMetaData>,
+    pub data: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AccountMetaData {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ProposalInstruction {
+    // Sized to the instructions it's created with, they can't be changed afterwards
+    pub fn get_size(instructions: &[InstructionData]) -> usize {
+        32 + 8
+            + 1
+            + 4
+            + instructions
+                .iter()
+                .map(|ix| 32 + (4 + ix.accounts.len() * (32 + 1 + 1)) + (4 + ix.data.len()))
+                .sum::<usize>()
+            + 1
+    }
+}
+
+impl InstructionData {
+    // Programs a proposal may call into, signed by the Faction PDA
+    // Calls back into this program are limited to the treasury withdrawals
+    pub fn is_whitelisted(&self) -> bool {
+        if self.program_id == crate::ID {
+            return self.data.len() >= 8
+                && (self.data[..8] == crate::instruction::WithdrawFromTreasury::DISCRIMINATOR
+                    || self.data[..8]
+                        == crate::instruction::WithdrawTokensFromTreasury::DISCRIMINATOR);
+        }
+        self.program_id == anchor_lang::system_program::ID
+            || self.program_id == anchor_spl::token::ID
+    }
+}
+
+impl From<&InstructionData> for Instruction {
+    fn from(ix: &InstructionData) -> Instruction {
+        Instruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data.clone(),
+        }
     }
 }
 
@@ -57,11 +879,23 @@ impl MaxSize for Proposal {
 pub struct ProposalVote {
     pub citizen: Pubkey,
     pub vote_amt: u64,
+    pub topic_amt: u64, // Part of vote_amt drawn from topic power for the proposal's category
+    pub weight: u64,    // What vote_amt added to the tally, see Proposal.vote_weight
+    pub option: u8,     // Index into Proposal.options
+    pub bump: u8,
 }
 
 impl MaxSize for ProposalVote {
     fn get_max_size() -> usize {
-        return 32 + 8;
+        32 + 8 + 8 + 8 + 1 + 1
+    }
+}
+
+impl ProposalVote {
+    // Splits a decrement into (general, topic) parts, general power is given back first
+    pub fn split_release(&self, amount: u64) -> (u64, u64) {
+        let general_amt = amount.min(self.vote_amt - self.topic_amt);
+        (general_amt, amount - general_amt)
     }
 }
 
@@ -70,11 +904,35 @@ pub struct VoteDelegation {
     pub citizen: Pubkey,
     pub delegate: Pubkey,
     pub vote_amt: u64,
+    pub forwarded_vote_amt: u64, // Part of vote_amt the delegate has forwarded on, liquid mode
+    pub expires_at: Option<i64>, // Anyone can unwind the delegation after this
+    pub category: ProposalCategory,
+    pub bump: u8,
 }
 
 impl MaxSize for VoteDelegation {
     fn get_max_size() -> usize {
-        return 32 + 32 + 8;
+        32 + 32 + 8 + 8 + (1 + 8) + 1 + 1
+    }
+}
+
+// One hop of forwarded (liquid) delegation, hanging off a VoteDelegation or another DelegationPath.
+// path is every citizen the power has passed through, the original delegator first
+#[account]
+pub struct DelegationPath {
+    pub parent: Pubkey,
+    pub citizen: Pubkey,
+    pub delegate: Pubkey,
+    pub vote_amt: u64,
+    pub forwarded_vote_amt: u64,
+    pub path: Vec<Pubkey>,
+    pub payer: Pubkey, // Gets the rent back once the hop is emptied
+    pub bump: u8,
+}
+
+impl MaxSize for DelegationPath {
+    fn get_max_size() -> usize {
+        32 + 32 + 32 + 8 + 8 + (4 + (MAX_DELEGATION_DEPTH as usize + 2) * 32) + 32 + 1
     }
 }
 
@@ -86,6 +944,8 @@ pub struct ResourceField {
     pub is_harvestable: bool,
     pub initial_claimant: Option<Pubkey>,
     pub times_developed: u64,
+    pub last_harvested: Option<i64>,
+    pub bump: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -94,13 +954,410 @@ pub struct Harvest {
     pub harvest: u64,
 }
 
+impl ResourceField {
+    // Name of the resource it yields, empty until it's developed
+    pub fn resource_name(&self) -> &str {
+        match &self.harvest {
+            Some(harvest) => harvest.resource.as_str(),
+            None => "",
+        }
+    }
+}
+
 impl MaxSize for ResourceField {
     fn get_max_size() -> usize {
-        return (4 + 21) + (1 + (4 + LONGEST_RESOURCE_NAME + 8)) + 9 + 1 + 33 + 8 + 8;
+        (4 + 21) + (1 + (4 + LONGEST_RESOURCE_NAME + 8)) + 9 + 1 + 33 + 8 + 9 + 1 + 8
         //added 8 bytes because it won't serialize the amount i think it is.
     }
 }
 
+// Pocket AMM, constant product pool between two resource mints (mint_a < mint_b)
+#[account]
+pub struct Pool {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_bps: u16,          // Taken off every swap and left in the pool for LPs
+    pub treasury_fee_bps: u16, // Taken off every swap and sent to the treasury, if there is one
+    pub treasury: Option<Pubkey>, // Faction treasury PDA that collects treasury_fee_bps
+    pub bump: u8,
+}
+
+impl MaxSize for Pool {
+    fn get_max_size() -> usize {
+        32 + 32 + 32 + 32 + 32 + 2 + 2 + 33 + 1
+    }
+}
+
 pub trait MaxSize {
     fn get_max_size() -> usize;
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anchor_lang::InstructionData as _;
+
+    pub(crate) fn faction() -> Faction {
+        Faction {
+            id: String::from("faction"),
+            max_voting_power: 100,
+            threshold_to_pass: 50,
+            unallocated_voting_power: 0,
+            governance_mint: None,
+            voting_mode: VotingMode::Linear,
+            conviction_half_life_seconds: 0,
+            conviction_spending_limit_bps: 0,
+            conviction_weight_bps: 0,
+            threshold_mode: ThresholdMode::Absolute,
+            quorum_bps: 0,
+            approval_bps: 0,
+            council: None,
+            proposal_min_voting_power: 0,
+            proposal_deposit: 0,
+            citizen_proposals_enabled: false,
+            liquid_delegation_depth: 0,
+            member_count: 1,
+            open_proposal_count: 1,
+            bump: 255,
+        }
+    }
+
+    // For/Against/Abstain DRAFT open from 0 to 100
+    pub(crate) fn proposal(faction: Pubkey) -> Proposal {
+        Proposal {
+            id: String::from("proposal"),
+            faction,
+            proposer: Pubkey::new_unique(),
+            vote_amt: 0,
+            status: ProposalStatus::DRAFT,
+            options: DEFAULT_PROPOSAL_OPTIONS
+                .iter()
+                .map(|o| String::from(*o))
+                .collect(),
+            tallies: vec![0; DEFAULT_PROPOSAL_OPTIONS.len()],
+            winning_option: None,
+            start_ts: 0,
+            end_ts: 100,
+            finalized_at: None,
+            snapshot_slot: 0,
+            voting_mode: VotingMode::Linear,
+            proposal_type: ProposalType::Standard,
+            requested_amount: 0,
+            conviction: 0,
+            conviction_updated_at: 0,
+            conviction_half_life_seconds: 0,
+            supply_snapshot: 100,
+            proposer_pledge: 0,
+            deposit: 0,
+            metadata: ProposalMetadata {
+                title: String::from("Proposal"),
+                uri: String::new(),
+                content_hash: [0; 32],
+            },
+            category: ProposalCategory::General,
+            bump: 255,
+        }
+    }
+
+    fn instruction(program_id: Pubkey, data: Vec<u8>) -> InstructionData {
+        InstructionData {
+            program_id,
+            accounts: vec![],
+            data,
+        }
+    }
+
+    pub(crate) fn citizen() -> Citizen {
+        Citizen {
+            mint: Pubkey::new_unique(),
+            faction: None,
+            delegated_voting_power: 0,
+            granted_voting_power: 0,
+            total_voting_power: 0,
+            max_pledged_voting_power: 0,
+            deposited_voting_power: 0,
+            locked_voting_power: 0,
+            topic_delegated_voting_power: [0; TOPIC_CATEGORIES],
+            topic_pledged_voting_power: [0; TOPIC_CATEGORIES],
+            vote_count: 0,
+            topic_vote_count: [0; TOPIC_CATEGORIES],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn citizen_counts_votes_unwinding_has_to_cover() {
+        let mut citizen = citizen();
+        citizen.open_vote(ProposalCategory::General).unwrap();
+        citizen.open_vote(ProposalCategory::Treasury).unwrap();
+        citizen.open_vote(ProposalCategory::Treasury).unwrap();
+        assert_eq!(citizen.votes_in(ProposalCategory::General), 3);
+        assert_eq!(citizen.votes_in(ProposalCategory::Treasury), 2);
+        assert_eq!(citizen.votes_in(ProposalCategory::Resource), 0);
+
+        citizen.close_vote(ProposalCategory::Treasury).unwrap();
+        assert_eq!(citizen.votes_in(ProposalCategory::General), 2);
+        assert_eq!(citizen.votes_in(ProposalCategory::Treasury), 1);
+        assert!(citizen.close_vote(ProposalCategory::Resource).is_err());
+    }
+
+    fn checkpoints() -> VotingPowerCheckpoints {
+        VotingPowerCheckpoints {
+            citizen: Pubkey::new_unique(),
+            head: 0,
+            checkpoints: vec![],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn full_checkpoint_history_refuses_young_overwrites() {
+        let mut history = checkpoints();
+        for i in 0..MAX_CHECKPOINTS as u64 {
+            history.record_at(i * 10, i + 1).unwrap();
+        }
+        assert!(history.record_at(1_000, 500).is_err());
+
+        // Nothing recorded so far was changed
+        for i in 0..MAX_CHECKPOINTS as u64 {
+            assert_eq!(history.power_at(i * 10).unwrap(), i + 1);
+        }
+        assert_eq!(history.power_at(1_000).unwrap(), MAX_CHECKPOINTS as u64);
+    }
+
+    #[test]
+    fn full_checkpoint_history_overwrites_once_old_enough() {
+        let mut history = checkpoints();
+        for i in 0..MAX_CHECKPOINTS as u64 {
+            history.record_at(i * 10, i + 1).unwrap();
+        }
+        let slot = MIN_CHECKPOINT_HISTORY_SLOTS;
+        history.record_at(slot, 500).unwrap();
+        assert_eq!(history.head, 1);
+        assert_eq!(history.power_at(slot).unwrap(), 500);
+        assert!(history.power_at(0).is_err());
+        assert_eq!(history.power_at(10).unwrap(), 2);
+    }
+
+    #[test]
+    fn checkpoint_ring_outlasts_the_history_window() {
+        // One refresh_lockup per interval can't wrap the ring inside the window
+        assert!(CHECKPOINT_INTERVAL_SLOTS * MAX_CHECKPOINTS as u64 >= MIN_CHECKPOINT_HISTORY_SLOTS);
+
+        let mut history = checkpoints();
+        for i in 0..=MAX_CHECKPOINTS as u64 {
+            history
+                .record_at(i * CHECKPOINT_INTERVAL_SLOTS, i + 1)
+                .unwrap();
+        }
+        assert_eq!(
+            history.latest_slot(),
+            Some(MAX_CHECKPOINTS as u64 * CHECKPOINT_INTERVAL_SLOTS)
+        );
+    }
+
+    #[test]
+    fn whitelist_allows_system_and_token_programs() {
+        assert!(instruction(anchor_lang::system_program::ID, vec![2, 0, 0, 0]).is_whitelisted());
+        assert!(instruction(anchor_spl::token::ID, vec![3]).is_whitelisted());
+        assert!(!instruction(Pubkey::new_unique(), vec![]).is_whitelisted());
+    }
+
+    #[test]
+    fn whitelist_limits_self_calls_to_treasury_withdrawals() {
+        let mut data = crate::instruction::WithdrawFromTreasury::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&100u64.to_le_bytes());
+        assert!(instruction(crate::ID, data).is_whitelisted());
+
+        let data = crate::instruction::WithdrawTokensFromTreasury { amount: 100 }.data();
+        assert!(instruction(crate::ID, data).is_whitelisted());
+
+        let data = crate::instruction::DepositToTreasury { amount: 100 }.data();
+        assert!(!instruction(crate::ID, data).is_whitelisted());
+        let data = crate::instruction::DeleteFaction {}.data();
+        assert!(!instruction(crate::ID, data).is_whitelisted());
+        assert!(!instruction(crate::ID, vec![]).is_whitelisted());
+        assert!(!instruction(crate::ID, vec![0; 4]).is_whitelisted());
+    }
+
+    #[test]
+    fn checkpoint_lookups_use_the_latest_at_or_before_the_slot() {
+        let mut history = checkpoints();
+        assert_eq!(history.power_at(5).unwrap(), 0);
+        history.record_at(10, 100).unwrap();
+        history.record_at(20, 40).unwrap();
+        assert_eq!(history.power_at(9).unwrap(), 0);
+        assert_eq!(history.power_at(10).unwrap(), 100);
+        assert_eq!(history.power_at(19).unwrap(), 100);
+        assert_eq!(history.power_at(20).unwrap(), 40);
+        assert_eq!(history.power_at(u64::MAX).unwrap(), 40);
+    }
+
+    #[test]
+    fn checkpoints_skip_no_ops_and_merge_within_a_slot() {
+        let mut history = checkpoints();
+        history.record_at(10, 100).unwrap();
+        history.record_at(15, 100).unwrap();
+        assert_eq!(history.checkpoints.len(), 1);
+
+        history.record_at(20, 50).unwrap();
+        history.record_at(20, 70).unwrap();
+        assert_eq!(history.checkpoints.len(), 2);
+        assert_eq!(history.power_at(20).unwrap(), 70);
+        assert_eq!(history.latest_slot(), Some(20));
+    }
+
+    const STATUSES: [ProposalStatus; 7] = [
+        ProposalStatus::DRAFT,
+        ProposalStatus::VOTING,
+        ProposalStatus::PASSED,
+        ProposalStatus::FAILED,
+        ProposalStatus::CANCELLED,
+        ProposalStatus::VETOED,
+        ProposalStatus::EXECUTED,
+    ];
+
+    #[test]
+    fn proposal_status_transitions() {
+        use ProposalStatus::*;
+        let allowed = [
+            (DRAFT, VOTING),
+            (DRAFT, CANCELLED),
+            (VOTING, PASSED),
+            (VOTING, FAILED),
+            (VOTING, CANCELLED),
+            (VOTING, VETOED),
+            (PASSED, VETOED),
+            (PASSED, EXECUTED),
+        ];
+        for from in STATUSES.iter() {
+            for to in STATUSES.iter() {
+                let expected = allowed.iter().any(|(a, b)| a == from && b == to);
+                assert_eq!(from.can_transition_to(to), expected);
+            }
+        }
+        assert!(DRAFT.is_open() && VOTING.is_open());
+        assert!(STATUSES[2..].iter().all(|status| !status.is_open()));
+    }
+
+    #[test]
+    fn illegal_transition_leaves_status_alone() {
+        let mut proposal = proposal(Pubkey::new_unique());
+        assert_eq!(
+            proposal.transition(ProposalStatus::EXECUTED).err(),
+            Some(error!(PocketErrors::IllegalTransition))
+        );
+        assert!(proposal.status == ProposalStatus::DRAFT);
+        assert!(proposal.transition(ProposalStatus::VOTING).unwrap() == ProposalStatus::DRAFT);
+        assert!(proposal.status == ProposalStatus::VOTING);
+    }
+
+    fn voting_proposal(faction: &Faction, tallies: Vec<u64>) -> Proposal {
+        let mut proposal = proposal(Pubkey::new_unique());
+        proposal.status = ProposalStatus::VOTING;
+        proposal.supply_snapshot = faction.max_voting_power;
+        proposal.vote_amt = tallies.iter().sum();
+        proposal.tallies = tallies;
+        proposal
+    }
+
+    #[test]
+    fn finalize_passes_for_over_the_absolute_threshold() {
+        let faction = faction();
+        let mut proposal = voting_proposal(&faction, vec![50, 10, 30]);
+        assert!(proposal.finalize(&faction, 1_000).unwrap() == ProposalStatus::VOTING);
+        assert!(proposal.status == ProposalStatus::PASSED);
+        assert_eq!(proposal.winning_option, Some(OPTION_FOR));
+        assert_eq!(proposal.finalized_at, Some(1_000));
+    }
+
+    #[test]
+    fn finalize_fails_under_the_absolute_threshold_or_when_against_wins() {
+        let faction = faction();
+        let mut proposal = voting_proposal(&faction, vec![49, 10, 0]);
+        proposal.finalize(&faction, 1_000).unwrap();
+        assert!(proposal.status == ProposalStatus::FAILED);
+        assert_eq!(proposal.winning_option, None);
+
+        let mut proposal = voting_proposal(&faction, vec![10, 60, 0]);
+        proposal.finalize(&faction, 1_000).unwrap();
+        assert!(proposal.status == ProposalStatus::FAILED);
+        assert_eq!(proposal.winning_option, Some(OPTION_AGAINST));
+    }
+
+    #[test]
+    fn abstain_never_wins_and_ties_fail() {
+        let faction = faction();
+        let mut proposal = voting_proposal(&faction, vec![50, 10, 90]);
+        assert_eq!(proposal.resolve(&faction), Some(OPTION_FOR));
+
+        let mut tied = voting_proposal(&faction, vec![60, 60, 0]);
+        assert_eq!(tied.resolve(&faction), None);
+        tied.finalize(&faction, 1_000).unwrap();
+        assert!(tied.status == ProposalStatus::FAILED);
+
+        proposal.finalize(&faction, 1_000).unwrap();
+        assert!(proposal.status == ProposalStatus::PASSED);
+    }
+
+    #[test]
+    fn multi_choice_winner_passes() {
+        let faction = faction();
+        let mut proposal = voting_proposal(&faction, vec![10, 20, 55, 5]);
+        proposal.options = vec![
+            String::from("A"),
+            String::from("B"),
+            String::from("C"),
+            String::from("D"),
+        ];
+        proposal.finalize(&faction, 1_000).unwrap();
+        assert!(proposal.status == ProposalStatus::PASSED);
+        assert_eq!(proposal.winning_option, Some(2));
+    }
+
+    #[test]
+    fn percentage_mode_needs_quorum_and_approval() {
+        let mut faction = faction();
+        faction.threshold_mode = ThresholdMode::Percentage;
+        faction.max_voting_power = 1_000;
+        faction.quorum_bps = 2_000; // 200 of 1000 have to vote
+        faction.approval_bps = 6_000; // the winner needs 60% of For + Against
+
+        // Quorum counts abstain, approval doesn't
+        let proposal = voting_proposal(&faction, vec![60, 40, 100]);
+        assert!(proposal.reached_quorum(&faction));
+        assert_eq!(proposal.resolve(&faction), Some(OPTION_FOR));
+
+        let proposal = voting_proposal(&faction, vec![59, 41, 100]);
+        assert!(proposal.reached_quorum(&faction));
+        assert_eq!(proposal.resolve(&faction), None);
+
+        let mut proposal = voting_proposal(&faction, vec![150, 0, 0]);
+        assert!(!proposal.reached_quorum(&faction));
+        proposal.finalize(&faction, 1_000).unwrap();
+        assert!(proposal.status == ProposalStatus::FAILED);
+        assert!(proposal.forfeits_deposit(&faction));
+    }
+
+    #[test]
+    fn conviction_proposals_fail_at_the_end_of_the_window() {
+        let faction = faction();
+        let mut proposal = voting_proposal(&faction, vec![100, 0, 0]);
+        proposal.proposal_type = ProposalType::Conviction;
+        proposal.finalize(&faction, 1_000).unwrap();
+        assert!(proposal.status == ProposalStatus::FAILED);
+        assert_eq!(proposal.winning_option, None);
+    }
+
+    #[test]
+    fn only_voting_proposals_finalize() {
+        let faction = faction();
+        let mut proposal = proposal(Pubkey::new_unique());
+        assert!(proposal.finalize(&faction, 1_000).is_err());
+        proposal.status = ProposalStatus::CANCELLED;
+        assert!(proposal.finalize(&faction, 1_000).is_err());
+    }
+}
@@ -1,10 +1,45 @@
-pub const SERVER_PUBKEY: &str = "pockmZEzU9m8bkgHMtA3bmHASDFKv8N3kwkEbvQdd9K";
+pub const SEEDS_CONFIG: &[u8; 6] = b"config";
 pub const SEEDS_FACTION: &[u8; 7] = b"faction";
 pub const SEEDS_CITIZEN: &[u8; 7] = b"citizen";
 pub const SEEDS_PROPOSAL: &[u8; 8] = b"proposal";
+pub const SEEDS_PROPOSAL_IX: &[u8; 11] = b"proposal_ix";
 pub const SEEDS_VOTE: &[u8; 4] = b"vote";
+pub const SEEDS_CHECKPOINTS: &[u8; 11] = b"checkpoints";
+pub const SEEDS_GOVERNANCE_VAULT: &[u8; 16] = b"governance_vault";
+pub const SEEDS_LOCKUP: &[u8; 6] = b"lockup";
 pub const SEEDS_DELEGATION: &[u8; 8] = b"delegate";
+pub const SEEDS_DELEGATION_PATH: &[u8; 15] = b"delegation_path";
 pub const SEEDS_RF: &[u8; 2] = b"rf";
+pub const SEEDS_RESOURCE_MINT: &[u8; 8] = b"resource";
+pub const SEEDS_MINT_AUTHORITY: &[u8; 14] = b"mint_authority";
+pub const SEEDS_POOL: &[u8; 4] = b"pool";
+pub const SEEDS_POOL_VAULT: &[u8; 10] = b"pool_vault";
+pub const SEEDS_LP_MINT: &[u8; 7] = b"lp_mint";
+
+pub const MAX_CHECKPOINTS: usize = 64; // Voting power history kept per citizen
+pub const MIN_CHECKPOINT_HISTORY_SLOTS: u64 = 1_512_000; // ~7 days, younger history is never overwritten
+pub const CHECKPOINT_INTERVAL_SLOTS: u64 = MIN_CHECKPOINT_HISTORY_SLOTS / MAX_CHECKPOINTS as u64; // ~2.6 hrs between refresh_lockup checkpoints
+
+pub const TOPIC_CATEGORIES: usize = 3; // ProposalCategory variants other than General
+pub const MAX_DELEGATION_DEPTH: u8 = 4; // Most times delegated power can be forwarded on
+
+pub const MAX_PROPOSAL_OPTIONS: usize = 5;
+pub const LONGEST_OPTION_LABEL: usize = 16; // character count of the longest option label
+pub const MAX_PROPOSAL_TITLE: usize = 64;
+pub const MAX_PROPOSAL_URI: usize = 200;
+pub const DEFAULT_PROPOSAL_OPTIONS: [&str; 3] = ["For", "Against", "Abstain"];
+pub const OPTION_FOR: u8 = 0;
+pub const OPTION_AGAINST: u8 = 1;
+pub const OPTION_ABSTAIN: u8 = 2;
+
+pub const BPS_DENOMINATOR: u64 = 10000;
+pub const MAX_POOL_FEE_BPS: u16 = 1000; // 10%, covers the pool fee and treasury cut combined
+pub const MINIMUM_LIQUIDITY: u64 = 1000; // LP locked in the pool on the first deposit, like Uniswap V2
+
+pub const DECAY_SCALE: u64 = 1_000_000_000; // Fixed point 1.0 for conviction decay
+
+pub const MAX_LOCKUP_SECONDS: i64 = 4 * 365 * 24 * 60 * 60; // 4 years
+pub const MAX_LOCKUP_BONUS_BPS: u64 = 10000; // Locking for the max duration doubles voting power
 
 pub const MAX_HARVEST_TYPES: usize = 1; // Max number of harvest types you can have in a resource field
 pub const LONGEST_RESOURCE_NAME: usize = 8; // character count of the longest resource name
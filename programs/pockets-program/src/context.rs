@@ -1,22 +1,29 @@
-use std::str::FromStr;
-
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
+use anchor_spl::token::Token;
 use anchor_spl::token::TokenAccount;
 
 use crate::account::*;
 use crate::constant::*;
+use crate::error::*;
+use crate::program::PocketsProgram;
 
 #[derive(Accounts)]
 #[instruction(id: String, starting_voting_power: u64, threshold: u64)]
 pub struct CreateFaction<'info> {
     #[account(
       mut,
-      address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+      address = config.authority
     )]
     pub server: Signer<'info>,
     pub system_program: Program<'info, System>,
 
+    #[account(
+      seeds=[SEEDS_CONFIG],
+      bump=config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
     #[account(
       init,
       payer=server,
@@ -31,52 +38,170 @@ pub struct CreateFaction<'info> {
 
     #[account(
       mut,
-      constraint = first_citizen.faction == None
+      seeds=[
+        SEEDS_CITIZEN,
+        first_citizen.mint.as_ref(),
+      ],
+      bump=first_citizen.bump,
+      constraint = first_citizen.faction.is_none()
     )]
     pub first_citizen: Account<'info, Citizen>,
+
+    #[account(
+      mut,
+      seeds=[
+        SEEDS_CHECKPOINTS,
+        first_citizen.key().as_ref(),
+      ],
+      bump=first_citizen_checkpoints.bump,
+    )]
+    pub first_citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
 pub struct DeleteFactionAccount<'info>{
   #[account(
     mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
   pub system_program: Program<'info, System>,
 
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
   #[account(
     mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
     close=server
   )]
   pub faction: Account<'info, Faction>,
 }
 
+#[derive(Accounts)]
+pub struct DisbandFaction<'info>{
+  #[account(
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
 #[derive(Accounts)]
 #[instruction(starting_voting_power: u64, threshold: u64)]
 pub struct UpdateFaction<'info> {
     #[account(
-      address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+      address = config.authority
     )]
     pub server: Signer<'info>,
     pub system_program: Program<'info, System>,
 
-    #[account(mut)]
+    #[account(
+      seeds=[SEEDS_CONFIG],
+      bump=config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+      mut,
+      seeds=[
+        SEEDS_FACTION,
+        faction.id.as_bytes(),
+      ],
+      bump=faction.bump,
+    )]
     pub faction: Account<'info, Faction>,
 }
 
+// Shared by the server-only faction settings
+#[derive(Accounts)]
+pub struct ConfigureFaction<'info> {
+  #[account(
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
 #[derive(Accounts)]
 pub struct TransferFromFaction<'info> {
   #[account(
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
   pub system_program: Program<'info, System>,
 
-  #[account(mut)]
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
   pub faction: Account<'info, Faction>,
-  #[account(mut)]
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
   pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+  )]
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 
@@ -84,11 +209,17 @@ pub struct TransferFromFaction<'info> {
 pub struct CreateCitizenRecord<'info> {
     #[account(
       mut,
-      address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+      address = config.authority
     )]
     pub server: Signer<'info>,
     pub system_program: Program<'info, System>,
 
+    #[account(
+      seeds=[SEEDS_CONFIG],
+      bump=config.bump,
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
     #[account(
       init, 
       payer=server,
@@ -101,358 +232,2223 @@ pub struct CreateCitizenRecord<'info> {
     )]
     pub citizen: Account<'info, Citizen>,
     pub mint: Account<'info, Mint>, 
+
+    #[account(
+      init,
+      payer=server,
+      seeds=[
+        SEEDS_CHECKPOINTS,
+        citizen.key().as_ref(),
+      ],
+      bump,
+      space=8+VotingPowerCheckpoints::get_max_size()
+    )]
+    pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
 pub struct DeleteCitizenAccount<'info>{
   #[account(
     mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
   pub system_program: Program<'info, System>,
 
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
   #[account(
     mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+    constraint = citizen.faction.is_none() @ PocketErrors::AlreadyInFaction,
     close=server
   )]
   pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+    close=server
+  )]
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 
 #[derive(Accounts)]
 pub struct JoinFaction<'info>{
   #[account(
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
   pub system_program: Program<'info, System>,
 
-  #[account(mut)]
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
   pub citizen: Account<'info, Citizen>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
   pub faction: Account<'info, Faction>,
 }
 
 #[derive(Accounts)]
 pub struct LeaveFaction<'info>{
   #[account(
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
   pub system_program: Program<'info, System>,
 
-  #[account(mut)]
-  pub citizen: Account<'info, Citizen>,
-  #[account(mut)]
-  pub faction: Account<'info, Faction>,
-}
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
 
-#[derive(Accounts)]
-#[instruction(id: String)]
-pub struct CreateProposal<'info> {
   #[account(
     mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
   )]
-  pub server: Signer<'info>,
-  pub system_program: Program<'info, System>,
-
+  pub citizen: Account<'info, Citizen>,
   #[account(
-    init, 
-    payer=server,
+    mut,
     seeds=[
-      SEEDS_PROPOSAL,
-      id.as_bytes(),
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
     ],
-    bump,
-    space=8+Proposal::get_max_size()
+    bump=faction.bump,
   )]
-  pub proposal: Account<'info, Proposal>,
   pub faction: Account<'info, Faction>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+  )]
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
-pub struct DeleteProposalAccount<'info>{
+pub struct SetGovernanceMint<'info> {
   #[account(
-    mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
-  pub system_program: Program<'info, System>,
 
   #[account(
-    mut,
-    close=server
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
   )]
-  pub proposal: Account<'info, Proposal>,
-}
+  pub config: Account<'info, ProgramConfig>,
 
-#[derive(Accounts)]
-#[instruction(id: String)]
-pub struct UpdateProposal<'info> {
   #[account(
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = faction.governance_mint.is_none() @ PocketErrors::GovernanceMintAlreadySet
   )]
-  pub server: Signer<'info>,
-  pub system_program: Program<'info, System>,
-  pub proposal: Account<'info, Proposal>,
+  pub faction: Account<'info, Faction>,
+  pub governance_mint: Account<'info, Mint>,
 }
 
 #[derive(Accounts)]
-pub struct Vote<'info> {
+pub struct CreateGovernanceVault<'info> {
   #[account(mut)]
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
   pub wallet_ata: Account<'info, TokenAccount>,
-
   pub system_program: Program<'info, System>,
-
-  #[account(mut)]
-  pub citizen: Account<'info, Citizen>,
+  pub token_program: Program<'info, Token>,
 
   #[account(
-    init, 
-    payer=wallet,
-    space=8+ProposalVote::get_max_size(),
     seeds=[
-      SEEDS_VOTE,
-      citizen.key().as_ref(),
-      proposal.key().as_ref(),
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
     ],
-    bump,
+    bump=citizen.bump,
+    constraint = citizen.faction == Some(faction.key())
   )]
-  pub vote: Account<'info, ProposalVote>,
-
+  pub citizen: Account<'info, Citizen>,
   #[account(
-    mut,
-    constraint = (proposal.status == ProposalStatus::VOTING) && (citizen.faction == Some(proposal.faction))
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = faction.governance_mint == Some(governance_mint.key()) @ PocketErrors::InvalidGovernanceMint
   )]
-  pub proposal: Account<'info, Proposal>,
+  pub faction: Account<'info, Faction>,
+  pub governance_mint: Account<'info, Mint>,
 
+  // Held by the citizen PDA so only the program can release it
   #[account(
-    constraint = proposal.faction == faction.key()
+    init,
+    payer=wallet,
+    seeds=[
+      SEEDS_GOVERNANCE_VAULT,
+      citizen.key().as_ref(),
+    ],
+    bump,
+    token::mint = governance_mint,
+    token::authority = citizen,
   )]
-  pub faction: Account<'info, Faction>,
+  pub vault: Account<'info, TokenAccount>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateVote<'info> {
+pub struct DepositGovernanceTokens<'info> {
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
-  pub wallet_ata: Account<'info, TokenAccount>,
-  pub system_program: Program<'info, System>,
-
-  #[account(mut)]
-  pub citizen: Account<'info, Citizen>,
+  pub wallet_ata: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
 
   #[account(
     mut,
-    constraint = vote.citizen == citizen.key()
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+    constraint = citizen.faction == Some(faction.key())
   )]
-  pub vote: Account<'info, ProposalVote>,
+  pub citizen: Box<Account<'info, Citizen>>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = faction.governance_mint == Some(vault.mint) @ PocketErrors::InvalidGovernanceMint
+  )]
+  pub faction: Box<Account<'info, Faction>>,
 
   #[account(
     mut,
-    constraint = citizen.faction == Some(proposal.faction)
+    token::authority = wallet,
+    token::mint = vault.mint,
   )]
-  pub proposal: Account<'info, Proposal>,
+  pub from: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_GOVERNANCE_VAULT,
+      citizen.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub vault: Box<Account<'info, TokenAccount>>,
 
   #[account(
-    constraint = proposal.faction == faction.key()
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
   )]
-  pub faction: Account<'info, Faction>,
+  pub citizen_checkpoints: Box<Account<'info, VotingPowerCheckpoints>>,
 }
 
 #[derive(Accounts)]
-pub struct CloseVoteAccount<'info> {
+pub struct WithdrawGovernanceTokens<'info> {
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
-  pub wallet_ata: Account<'info, TokenAccount>,
-  pub system_program: Program<'info, System>,
-
-  #[account(mut)]
-  pub citizen: Account<'info, Citizen>,
+  pub wallet_ata: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
 
   #[account(
     mut,
-    close = wallet,
-    constraint = vote.citizen == citizen.key()
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+    constraint = citizen.faction == Some(faction.key())
   )]
-  pub vote: Account<'info, ProposalVote>,
-
+  pub citizen: Box<Account<'info, Citizen>>,
   #[account(
-    constraint = (proposal.status == ProposalStatus::VOTING) && (citizen.faction == Some(proposal.faction))
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
   )]
-  pub proposal: Account<'info, Proposal>,
+  pub faction: Box<Account<'info, Faction>>,
 
   #[account(
-    constraint = proposal.faction == faction.key()
+    mut,
+    seeds=[
+      SEEDS_GOVERNANCE_VAULT,
+      citizen.key().as_ref(),
+    ],
+    bump,
   )]
-  pub faction: Account<'info, Faction>,
+  pub vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    token::mint = vault.mint,
+  )]
+  pub to: Box<Account<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+  )]
+  pub citizen_checkpoints: Box<Account<'info, VotingPowerCheckpoints>>,
 }
 
 #[derive(Accounts)]
-pub struct TransferVotes<'info> {
+pub struct CreateLockup<'info> {
+  #[account(mut)]
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
   pub wallet_ata: Account<'info, TokenAccount>,
   pub system_program: Program<'info, System>,
 
-  #[account(mut)]
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
   pub citizen: Account<'info, Citizen>,
-  
+
+  #[account(
+    init,
+    payer=wallet,
+    seeds=[
+      SEEDS_LOCKUP,
+      citizen.key().as_ref(),
+    ],
+    bump,
+    space=8+Lockup::get_max_size()
+  )]
+  pub lockup: Account<'info, Lockup>,
+
   #[account(
     mut,
-    constraint = citizen.faction == vote_recepient.faction
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
   )]
-  pub vote_recepient: Account<'info, Citizen>,
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
-pub struct DelegateVote<'info> {
-  #[account(mut)]
+pub struct ExtendLockup<'info> {
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
   pub wallet_ata: Account<'info, TokenAccount>,
-  pub system_program: Program<'info, System>,
 
-  #[account(mut)]
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
   pub citizen: Account<'info, Citizen>,
-  
+
   #[account(
     mut,
-    constraint = citizen.faction == vote_recepient.faction
+    seeds=[
+      SEEDS_LOCKUP,
+      citizen.key().as_ref(),
+    ],
+    bump=lockup.bump,
   )]
-  pub vote_recepient: Account<'info, Citizen>,
+  pub lockup: Account<'info, Lockup>,
 
   #[account(
-    init,
-    payer=wallet,
+    mut,
     seeds=[
-      SEEDS_DELEGATION,
+      SEEDS_CHECKPOINTS,
       citizen.key().as_ref(),
-      vote_recepient.key().as_ref()
     ],
-    bump,
-    space=8+VoteDelegation::get_max_size(),
+    bump=citizen_checkpoints.bump,
   )]
-  pub delegation_record: Account<'info, VoteDelegation>,
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
-pub struct DeleteVoteDelegation<'info>{
+pub struct RefreshLockup<'info> {
   #[account(
     mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
   )]
-  pub server: Signer<'info>,
-  pub system_program: Program<'info, System>,
+  pub citizen: Account<'info, Citizen>,
 
   #[account(
     mut,
-    close=server
+    seeds=[
+      SEEDS_LOCKUP,
+      citizen.key().as_ref(),
+    ],
+    bump=lockup.bump,
+  )]
+  pub lockup: Account<'info, Lockup>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
   )]
-  pub delegation: Account<'info, VoteDelegation>,
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
-pub struct AdjustDelegation<'info> {
+pub struct Unlock<'info> {
   #[account(mut)]
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
   pub wallet_ata: Account<'info, TokenAccount>,
-  pub system_program: Program<'info, System>,
 
-  #[account(mut)]
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
   pub citizen: Account<'info, Citizen>,
-  
+
   #[account(
     mut,
-    constraint = citizen.faction == vote_recepient.faction
+    seeds=[
+      SEEDS_LOCKUP,
+      citizen.key().as_ref(),
+    ],
+    bump=lockup.bump,
+    close=wallet
   )]
-  pub vote_recepient: Account<'info, Citizen>,
+  pub lockup: Account<'info, Lockup>,
 
   #[account(
     mut,
-    constraint = (delegation_record.citizen == citizen.key()) && (delegation_record.delegate == vote_recepient.key())
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
   )]
-  pub delegation_record: Account<'info, VoteDelegation>,
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
 }
 
 #[derive(Accounts)]
-#[instruction(id: String)]
-pub struct DiscoverRF<'info> {
+pub struct DepositToTreasury<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+    constraint = citizen.faction == Some(faction.key())
+  )]
+  pub citizen: Account<'info, Citizen>,
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Account<'info, Faction>,
+
   #[account(
     mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    seeds=[
+      SEEDS_FACTION,
+      faction.key().as_ref(),
+    ],
+    bump,
   )]
-  pub server: Signer<'info>,
+  pub treasury: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositTokensToTreasury<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+    constraint = citizen.faction == Some(faction.key())
+  )]
+  pub citizen: Account<'info, Citizen>,
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Account<'info, Faction>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub treasury: SystemAccount<'info>,
+
+  #[account(
+    mut,
+    token::authority = wallet,
+  )]
+  pub from: Account<'info, TokenAccount>,
+  #[account(
+    mut,
+    token::authority = treasury,
+    token::mint = from.mint,
+  )]
+  pub treasury_token_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromTreasury<'info> {
+  // Only signs when a passed proposal is executed
+  #[account(
+    signer,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Account<'info, Faction>,
   pub system_program: Program<'info, System>,
 
   #[account(
-    init,
-    payer=server,
+    mut,
     seeds=[
-      SEEDS_RF,
-      id.as_bytes(),
+      SEEDS_FACTION,
+      faction.key().as_ref(),
     ],
     bump,
-    space=8+ResourceField::get_max_size()
   )]
-  pub rf: Account<'info, ResourceField>,
+  pub treasury: SystemAccount<'info>,
+
+  /// CHECK: Any account can receive SOL, the proposal decides who
+  #[account(mut)]
+  pub recipient: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct DeleteResourceField<'info>{
+pub struct WithdrawTokensFromTreasury<'info> {
+  // Only signs when a passed proposal is executed
+  #[account(
+    signer,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Account<'info, Faction>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub treasury: SystemAccount<'info>,
+
+  #[account(
+    mut,
+    token::authority = treasury,
+  )]
+  pub treasury_token_account: Account<'info, TokenAccount>,
+  #[account(
+    mut,
+    token::mint = treasury_token_account.mint,
+  )]
+  pub recipient: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct CreateProposal<'info> {
   #[account(
     mut,
-    address = Pubkey::from_str(SERVER_PUBKEY).unwrap()
+    address = config.authority
   )]
   pub server: Signer<'info>,
   pub system_program: Program<'info, System>,
 
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    init, 
+    payer=server,
+    seeds=[
+      SEEDS_PROPOSAL,
+      id.as_bytes(),
+    ],
+    bump,
+    space=8+Proposal::get_max_size()
+  )]
+  pub proposal: Account<'info, Proposal>,
   #[account(
     mut,
-    close=server
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
   )]
-  pub resource_field: Account<'info, ResourceField>,
-}
+  pub faction: Account<'info, Faction>,
 
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      proposer.mint.as_ref(),
+    ],
+    bump=proposer.bump,
+    constraint = proposer.faction == Some(faction.key())
+  )]
+  pub proposer: Account<'info, Citizen>,
+}
 
 #[derive(Accounts)]
-pub struct DevelopRF<'info> {
+#[instruction(id: String)]
+pub struct CreateCitizenProposal<'info> {
   #[account(mut)]
   pub wallet: Signer<'info>,
   #[account(
     token::authority = wallet,
-    token::mint = citizen.mint.key()
+    token::mint = proposer.mint.key(),
+    constraint = wallet_ata.amount == 1
   )]
-  pub wallet_ata: Account<'info, TokenAccount>,
+  pub wallet_ata: Box<Account<'info, TokenAccount>>,
   pub system_program: Program<'info, System>,
-  pub citizen: Account<'info, Citizen>,
 
-  #[account(mut)]
-  pub rf: Account<'info, ResourceField>,
   #[account(
-    constraint = citizen.faction == Some(faction.key())
+    init,
+    payer=wallet,
+    seeds=[
+      SEEDS_PROPOSAL,
+      id.as_bytes(),
+    ],
+    bump,
+    space=8+Proposal::get_max_size()
   )]
-  pub faction: Account<'info, Faction>,
-}
\ No newline at end of file
+  pub proposal: Box<Account<'info, Proposal>>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Box<Account<'info, Faction>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      proposer.mint.as_ref(),
+    ],
+    bump=proposer.bump,
+    constraint = proposer.faction == Some(faction.key())
+  )]
+  pub proposer: Box<Account<'info, Citizen>>,
+}
+
+#[derive(Accounts)]
+pub struct SettleProposalDeposit<'info> {
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.proposer == proposer.key() @ PocketErrors::NotProposer
+  )]
+  pub proposal: Box<Account<'info, Proposal>>,
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Box<Account<'info, Faction>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      proposer.mint.as_ref(),
+    ],
+    bump=proposer.bump,
+  )]
+  pub proposer: Box<Account<'info, Citizen>>,
+
+  // Refunds go to whoever currently holds the proposer citizen
+  #[account(mut)]
+  pub proposer_wallet: SystemAccount<'info>,
+  #[account(
+    token::authority = proposer_wallet,
+    token::mint = proposer.mint.key(),
+    constraint = proposer_ata.amount == 1
+  )]
+  pub proposer_ata: Box<Account<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub treasury: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenProposal<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = proposer.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      proposer.mint.as_ref(),
+    ],
+    bump=proposer.bump,
+  )]
+  pub proposer: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.proposer == proposer.key() @ PocketErrors::NotProposer
+  )]
+  pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+#[instruction(hold_up_seconds: i64, instructions: Vec<InstructionData>)]
+pub struct CreateProposalInstructions<'info> {
+  #[account(
+    mut,
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_PROPOSAL_IX,
+      proposal.key().as_ref(),
+    ],
+    bump,
+    space=8+ProposalInstruction::get_size(&instructions)
+  )]
+  pub proposal_instruction: Account<'info, ProposalInstruction>,
+
+  #[account(
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.status == ProposalStatus::DRAFT @ PocketErrors::VotingAlreadyStarted
+  )]
+  pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.status == ProposalStatus::PASSED @ PocketErrors::ProposalNotPassed
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL_IX,
+      proposal.key().as_ref(),
+    ],
+    bump=proposal_instruction.bump,
+    constraint = !proposal_instruction.executed @ PocketErrors::ProposalAlreadyExecuted
+  )]
+  pub proposal_instruction: Account<'info, ProposalInstruction>,
+
+  // Not mut, instructions may write to the faction and we don't want to clobber it on exit
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteProposalAccount<'info>{
+  #[account(
+    mut,
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = !proposal.status.is_open() @ PocketErrors::ProposalNotFinalized,
+    constraint = proposal.deposit == 0 && proposal.proposer_pledge == 0 @ PocketErrors::DepositNotSettled,
+    close=server
+  )]
+  pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProposal<'info> {
+  #[account(
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = proposer.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      proposer.mint.as_ref(),
+    ],
+    bump=proposer.bump,
+  )]
+  pub proposer: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.proposer == proposer.key() @ PocketErrors::NotProposer
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct VetoProposal<'info> {
+  pub council: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key(),
+    constraint = faction.council == Some(council.key()) @ PocketErrors::NotCouncil
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.status == ProposalStatus::VOTING
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConviction<'info> {
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = proposal.status == ProposalStatus::VOTING
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub treasury: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Vote<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    init, 
+    payer=wallet,
+    space=8+ProposalVote::get_max_size(),
+    seeds=[
+      SEEDS_VOTE,
+      citizen.key().as_ref(),
+      proposal.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub vote: Account<'info, ProposalVote>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = (proposal.status == ProposalStatus::VOTING) && (citizen.faction == Some(proposal.faction))
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+
+  #[account(
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+  )]
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVote<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_VOTE,
+      citizen.key().as_ref(),
+      proposal.key().as_ref(),
+    ],
+    bump=vote.bump,
+    constraint = vote.citizen == citizen.key()
+  )]
+  pub vote: Account<'info, ProposalVote>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = citizen.faction == Some(proposal.faction)
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+
+  #[account(
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+  )]
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVoteAccount<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_VOTE,
+      citizen.key().as_ref(),
+      proposal.key().as_ref(),
+    ],
+    bump=vote.bump,
+    close = wallet,
+    constraint = vote.citizen == citizen.key()
+  )]
+  pub vote: Account<'info, ProposalVote>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_PROPOSAL,
+      proposal.id.as_bytes(),
+    ],
+    bump=proposal.bump,
+    constraint = citizen.faction == Some(proposal.faction)
+  )]
+  pub proposal: Account<'info, Proposal>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = proposal.faction == faction.key()
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct TransferVotes<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+  
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+    constraint = citizen.faction == vote_recepient.faction
+  )]
+  pub vote_recepient: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      citizen.key().as_ref(),
+    ],
+    bump=citizen_checkpoints.bump,
+  )]
+  pub citizen_checkpoints: Account<'info, VotingPowerCheckpoints>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Account<'info, VotingPowerCheckpoints>,
+}
+
+#[derive(Accounts)]
+#[instruction(vote_amt: u64, expires_at: Option<i64>, category: ProposalCategory)]
+pub struct DelegateVote<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+  
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+    constraint = citizen.faction == vote_recepient.faction
+  )]
+  pub vote_recepient: Account<'info, Citizen>,
+
+  #[account(
+    init,
+    payer=wallet,
+    seeds=[
+      SEEDS_DELEGATION,
+      citizen.key().as_ref(),
+      vote_recepient.key().as_ref(),
+      &[category as u8],
+    ],
+    bump,
+    space=8+VoteDelegation::get_max_size(),
+  )]
+  pub delegation_record: Account<'info, VoteDelegation>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Account<'info, VotingPowerCheckpoints>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Box<Account<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+  )]
+  pub vote_recepient: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_DELEGATION,
+      citizen.key().as_ref(),
+      vote_recepient.key().as_ref(),
+      &[delegation_record.category as u8],
+    ],
+    bump=delegation_record.bump,
+    close=wallet
+  )]
+  pub delegation_record: Box<Account<'info, VoteDelegation>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Box<Account<'info, VotingPowerCheckpoints>>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireDelegation<'info> {
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+  )]
+  pub vote_recepient: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_DELEGATION,
+      citizen.key().as_ref(),
+      vote_recepient.key().as_ref(),
+      &[delegation_record.category as u8],
+    ],
+    bump=delegation_record.bump,
+  )]
+  pub delegation_record: Box<Account<'info, VoteDelegation>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Box<Account<'info, VotingPowerCheckpoints>>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteVoteDelegation<'info>{
+  // Server, or the wallet holding the delegator citizen
+  #[account(mut)]
+  pub closer: Signer<'info>,
+  // Only needed when the delegator is closing
+  #[account(
+    token::authority = closer,
+    token::mint = citizen.mint.key()
+  )]
+  pub closer_ata: Option<Box<Account<'info, TokenAccount>>>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Box<Account<'info, ProgramConfig>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+  )]
+  pub vote_recepient: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_DELEGATION,
+      citizen.key().as_ref(),
+      vote_recepient.key().as_ref(),
+      &[delegation.category as u8],
+    ],
+    bump=delegation.bump,
+    close=closer
+  )]
+  pub delegation: Box<Account<'info, VoteDelegation>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Box<Account<'info, VotingPowerCheckpoints>>,
+}
+
+#[derive(Accounts)]
+pub struct AdjustDelegation<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+  
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+    constraint = citizen.faction == vote_recepient.faction
+  )]
+  pub vote_recepient: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_DELEGATION,
+      delegation_record.citizen.as_ref(),
+      delegation_record.delegate.as_ref(),
+      &[delegation_record.category as u8],
+    ],
+    bump=delegation_record.bump,
+    constraint = (delegation_record.citizen == citizen.key()) && (delegation_record.delegate == vote_recepient.key())
+  )]
+  pub delegation_record: Account<'info, VoteDelegation>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Account<'info, VotingPowerCheckpoints>,
+}
+
+#[derive(Accounts)]
+pub struct ReturnDelegation<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = vote_recepient.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+  )]
+  pub vote_recepient: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_DELEGATION,
+      citizen.key().as_ref(),
+      vote_recepient.key().as_ref(),
+      &[delegation_record.category as u8],
+    ],
+    bump=delegation_record.bump,
+  )]
+  pub delegation_record: Account<'info, VoteDelegation>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Account<'info, VotingPowerCheckpoints>,
+}
+
+#[derive(Accounts)]
+pub struct ForwardDelegation<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Box<Account<'info, TokenAccount>>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+    constraint = citizen.faction == Some(faction.key())
+  )]
+  pub citizen: Box<Account<'info, Citizen>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CITIZEN,
+      vote_recepient.mint.as_ref(),
+    ],
+    bump=vote_recepient.bump,
+    constraint = citizen.faction == vote_recepient.faction
+  )]
+  pub vote_recepient: Box<Account<'info, Citizen>>,
+
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Box<Account<'info, Faction>>,
+
+  /// CHECK: VoteDelegation or DelegationPath, deserialized and checked in the handler
+  #[account(mut)]
+  pub parent: UncheckedAccount<'info>,
+
+  #[account(
+    init,
+    payer=wallet,
+    seeds=[
+      SEEDS_DELEGATION_PATH,
+      parent.key().as_ref(),
+      vote_recepient.key().as_ref(),
+    ],
+    bump,
+    space=8+DelegationPath::get_max_size(),
+  )]
+  pub delegation_path: Box<Account<'info, DelegationPath>>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_CHECKPOINTS,
+      vote_recepient.key().as_ref(),
+    ],
+    bump=vote_recepient_checkpoints.bump,
+  )]
+  pub vote_recepient_checkpoints: Box<Account<'info, VotingPowerCheckpoints>>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: String)]
+pub struct DiscoverRF<'info> {
+  #[account(
+    mut,
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_RF,
+      id.as_bytes(),
+    ],
+    bump,
+    space=8+ResourceField::get_max_size()
+  )]
+  pub rf: Account<'info, ResourceField>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteResourceField<'info>{
+  #[account(
+    mut,
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_RF,
+      resource_field.id.as_bytes(),
+    ],
+    bump=resource_field.bump,
+    close=server
+  )]
+  pub resource_field: Account<'info, ResourceField>,
+}
+
+
+#[derive(Accounts)]
+pub struct DevelopRF<'info> {
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_RF,
+      rf.id.as_bytes(),
+    ],
+    bump=rf.bump,
+  )]
+  pub rf: Account<'info, ResourceField>,
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+    constraint = citizen.faction == Some(faction.key())
+  )]
+  pub faction: Account<'info, Faction>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestRF<'info> {
+  pub wallet: Signer<'info>,
+  #[account(
+    token::authority = wallet,
+    token::mint = citizen.mint.key(),
+    constraint = wallet_ata.amount == 1
+  )]
+  pub wallet_ata: Account<'info, TokenAccount>,
+  pub system_program: Program<'info, System>,
+  pub token_program: Program<'info, Token>,
+  #[account(
+    seeds=[
+      SEEDS_CITIZEN,
+      citizen.mint.as_ref(),
+    ],
+    bump=citizen.bump,
+  )]
+  pub citizen: Account<'info, Citizen>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_RF,
+      rf.id.as_bytes(),
+    ],
+    bump=rf.bump,
+    constraint = rf.initial_claimant == Some(citizen.mint) @ PocketErrors::NotResourceFieldClaimant,
+    constraint = rf.is_harvestable @ PocketErrors::ResourceFieldNotDeveloped
+  )]
+  pub rf: Account<'info, ResourceField>,
+
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_RESOURCE_MINT,
+      rf.resource_name().as_bytes(),
+    ],
+    bump,
+  )]
+  pub resource_mint: Account<'info, Mint>,
+  #[account(
+    mut,
+    token::mint = resource_mint,
+    token::authority = wallet,
+  )]
+  pub resource_token_account: Account<'info, TokenAccount>,
+
+  /// CHECK: PDA that signs for every resource mint
+  #[account(
+    seeds=[SEEDS_MINT_AUTHORITY],
+    bump,
+  )]
+  pub mint_authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(resource: String)]
+pub struct RegisterResourceMint<'info> {
+  #[account(
+    mut,
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_RESOURCE_MINT,
+      resource.as_bytes(),
+    ],
+    bump,
+    mint::decimals = 0,
+    mint::authority = mint_authority,
+  )]
+  pub resource_mint: Account<'info, Mint>,
+
+  /// CHECK: PDA that signs for every resource mint
+  #[account(
+    seeds=[SEEDS_MINT_AUTHORITY],
+    bump,
+  )]
+  pub mint_authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(resource_a: String, resource_b: String)]
+pub struct CreatePool<'info> {
+  #[account(
+    mut,
+    address = config.authority
+  )]
+  pub server: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Box<Account<'info, ProgramConfig>>,
+
+  #[account(
+    seeds=[
+      SEEDS_RESOURCE_MINT,
+      resource_a.as_bytes(),
+    ],
+    bump,
+    constraint = mint_a.key() < mint_b.key() @ PocketErrors::InvalidPoolMints
+  )]
+  pub mint_a: Box<Account<'info, Mint>>,
+  #[account(
+    seeds=[
+      SEEDS_RESOURCE_MINT,
+      resource_b.as_bytes(),
+    ],
+    bump,
+  )]
+  pub mint_b: Box<Account<'info, Mint>>,
+
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_POOL,
+      mint_a.key().as_ref(),
+      mint_b.key().as_ref(),
+    ],
+    bump,
+    space=8+Pool::get_max_size()
+  )]
+  pub pool: Box<Account<'info, Pool>>,
+
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_POOL_VAULT,
+      pool.key().as_ref(),
+      mint_a.key().as_ref(),
+    ],
+    bump,
+    token::mint = mint_a,
+    token::authority = pool,
+  )]
+  pub vault_a: Box<Account<'info, TokenAccount>>,
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_POOL_VAULT,
+      pool.key().as_ref(),
+      mint_b.key().as_ref(),
+    ],
+    bump,
+    token::mint = mint_b,
+    token::authority = pool,
+  )]
+  pub vault_b: Box<Account<'info, TokenAccount>>,
+
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_LP_MINT,
+      pool.key().as_ref(),
+    ],
+    bump,
+    mint::decimals = 0,
+    mint::authority = pool,
+  )]
+  pub lp_mint: Box<Account<'info, Mint>>,
+  // Holds the MINIMUM_LIQUIDITY locked on the first deposit, nothing moves it back out
+  #[account(
+    init,
+    payer=server,
+    seeds=[
+      SEEDS_POOL_VAULT,
+      pool.key().as_ref(),
+      lp_mint.key().as_ref(),
+    ],
+    bump,
+    token::mint = lp_mint,
+    token::authority = pool,
+  )]
+  pub lp_vault: Box<Account<'info, TokenAccount>>,
+
+  // Optional, the faction whose treasury collects the treasury fee
+  #[account(
+    seeds=[
+      SEEDS_FACTION,
+      faction.id.as_bytes(),
+    ],
+    bump=faction.bump,
+  )]
+  pub faction: Option<Box<Account<'info, Faction>>>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+  pub wallet: Signer<'info>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[
+      SEEDS_POOL,
+      pool.mint_a.as_ref(),
+      pool.mint_b.as_ref(),
+    ],
+    bump=pool.bump,
+    has_one = vault_a,
+    has_one = vault_b,
+    has_one = lp_mint,
+  )]
+  pub pool: Box<Account<'info, Pool>>,
+  #[account(mut)]
+  pub vault_a: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub vault_b: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub lp_mint: Box<Account<'info, Mint>>,
+  #[account(
+    mut,
+    seeds=[
+      SEEDS_POOL_VAULT,
+      pool.key().as_ref(),
+      lp_mint.key().as_ref(),
+    ],
+    bump,
+  )]
+  pub lp_vault: Box<Account<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    token::authority = wallet,
+    token::mint = pool.mint_a,
+  )]
+  pub user_a: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    token::authority = wallet,
+    token::mint = pool.mint_b,
+  )]
+  pub user_b: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    token::mint = lp_mint,
+  )]
+  pub user_lp: Box<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+  pub wallet: Signer<'info>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[
+      SEEDS_POOL,
+      pool.mint_a.as_ref(),
+      pool.mint_b.as_ref(),
+    ],
+    bump=pool.bump,
+    has_one = vault_a,
+    has_one = vault_b,
+    has_one = lp_mint,
+  )]
+  pub pool: Box<Account<'info, Pool>>,
+  #[account(mut)]
+  pub vault_a: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub vault_b: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub lp_mint: Box<Account<'info, Mint>>,
+
+  #[account(
+    mut,
+    token::mint = pool.mint_a,
+  )]
+  pub user_a: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    token::mint = pool.mint_b,
+  )]
+  pub user_b: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    token::authority = wallet,
+    token::mint = lp_mint,
+  )]
+  pub user_lp: Box<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+  pub wallet: Signer<'info>,
+  pub token_program: Program<'info, Token>,
+
+  #[account(
+    seeds=[
+      SEEDS_POOL,
+      pool.mint_a.as_ref(),
+      pool.mint_b.as_ref(),
+    ],
+    bump=pool.bump,
+    has_one = vault_a,
+    has_one = vault_b,
+  )]
+  pub pool: Box<Account<'info, Pool>>,
+  #[account(mut)]
+  pub vault_a: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub vault_b: Box<Account<'info, TokenAccount>>,
+
+  #[account(
+    mut,
+    token::authority = wallet,
+    token::mint = pool.mint_a,
+  )]
+  pub user_a: Box<Account<'info, TokenAccount>>,
+  #[account(
+    mut,
+    token::authority = wallet,
+    token::mint = pool.mint_b,
+  )]
+  pub user_b: Box<Account<'info, TokenAccount>>,
+
+  // Required when the pool has a treasury fee, must be the treasury's account for the input mint
+  #[account(mut)]
+  pub treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+  #[account(mut)]
+  pub upgrade_authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+
+  #[account(
+    init,
+    payer=upgrade_authority,
+    seeds=[SEEDS_CONFIG],
+    bump,
+    space=8+ProgramConfig::get_max_size()
+  )]
+  pub config: Account<'info, ProgramConfig>,
+
+  // Only the program's upgrade authority can bootstrap the config
+  #[account(
+    constraint = program.programdata_address()? == Some(program_data.key())
+  )]
+  pub program: Program<'info, PocketsProgram>,
+  #[account(
+    constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key())
+  )]
+  pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+  #[account(
+    address = config.authority
+  )]
+  pub authority: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+  )]
+  pub config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+  pub new_authority: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds=[SEEDS_CONFIG],
+    bump=config.bump,
+    constraint = config.pending_authority == Some(new_authority.key()) @ PocketErrors::NotPendingAuthority
+  )]
+  pub config: Account<'info, ProgramConfig>,
+}
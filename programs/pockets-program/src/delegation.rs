@@ -0,0 +1,334 @@
+use crate::account::*;
+use crate::constant::*;
+use crate::error::*;
+use crate::math::*;
+use anchor_lang::prelude::*;
+
+// Frees up `amount` of the delegate's voting power in `category`, pulling it out of their votes if
+// it's pledged. remaining_accounts are (ProposalVote, Proposal) pairs for every one of the delegate's
+// votes that could hold the power (see Citizen.votes_in), so the cut is spread proportionally across
+// all of them and whoever calls this can't pick which proposals lose it. Topic power can only come
+// out of votes on proposals in that category. Tallies only change while the proposal is still open,
+// after that the result stands and only the pledge is released. Returns how much was pulled out of votes
+pub fn unwind_delegate_votes<'info>(
+    delegate: &mut Citizen,
+    delegate_key: Pubkey,
+    category: ProposalCategory,
+    amount: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    let is_topic = category.topic_index().is_some();
+    let free_voting_power = delegate.free_delegated_voting_power(category)?;
+    if free_voting_power >= amount {
+        return Ok(0);
+    }
+    let deficit = amount - free_voting_power;
+
+    let mut votes: Vec<(Account<'info, ProposalVote>, Account<'info, Proposal>)> = vec![];
+    for pair in remaining_accounts.chunks(2) {
+        if pair.len() != 2 {
+            return err!(PocketErrors::InvalidUnwindAccounts);
+        }
+        let vote: Account<ProposalVote> = Account::try_from(&pair[0])?;
+        let proposal: Account<Proposal> = Account::try_from(&pair[1])?;
+
+        let expected = Pubkey::create_program_address(
+            &[
+                SEEDS_VOTE,
+                delegate_key.as_ref(),
+                proposal.key().as_ref(),
+                &[vote.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| error!(PocketErrors::InvalidUnwindAccounts))?;
+        if vote.key() != expected
+            || vote.citizen != delegate_key
+            || votes.iter().any(|(v, _)| v.key() == vote.key())
+            || (is_topic && proposal.category != category)
+        {
+            return err!(PocketErrors::InvalidUnwindAccounts);
+        }
+        votes.push((vote, proposal));
+    }
+    if votes.len() as u64 != delegate.votes_in(category) {
+        return err!(PocketErrors::MissingUnwindVotes);
+    }
+
+    // The part of each vote that was pledged out of the power being freed
+    let mut pledged: Vec<u64> = vec![];
+    for (vote, _) in votes.iter() {
+        if is_topic {
+            pledged.push(vote.topic_amt);
+        } else {
+            pledged.push(safe_sub(vote.vote_amt, vote.topic_amt)?);
+        }
+    }
+    let mut total_votes: u64 = 0;
+    for part in pledged.iter() {
+        total_votes = safe_add(total_votes, *part)?;
+    }
+    if total_votes < deficit {
+        return err!(PocketErrors::InvalidUnwindAccounts);
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut unwound: u64 = 0;
+    for ((vote, proposal), part) in votes.iter_mut().zip(pledged) {
+        if unwound >= deficit {
+            break;
+        }
+        // Round up so the cuts always cover the deficit
+        let share =
+            mul_div_ceil(part, deficit, total_votes).ok_or(error!(PocketErrors::Overflow))?;
+        let cut = share.min(part).min(deficit - unwound);
+
+        if proposal.is_voting_open(now) {
+            proposal.accrue_conviction(now)?;
+            proposal.remove_vote(vote)?;
+            vote.vote_amt = safe_sub(vote.vote_amt, cut)?;
+            vote.weight = proposal.vote_weight(vote.vote_amt);
+            proposal.add_vote(vote)?;
+        } else {
+            vote.vote_amt = safe_sub(vote.vote_amt, cut)?;
+        }
+        if is_topic {
+            vote.topic_amt = safe_sub(vote.topic_amt, cut)?;
+            delegate.release_voting_power(category, 0, cut)?;
+        } else {
+            delegate.release_voting_power(category, cut, 0)?;
+        }
+        unwound = safe_add(unwound, cut)?;
+
+        vote.exit(&crate::ID)?;
+        proposal.exit(&crate::ID)?;
+    }
+    Ok(unwound)
+}
+
+// Hands the delegated power back to the delegator, the delegate must have it free
+pub fn release_delegation(
+    citizen: &mut Citizen,
+    delegate: &mut Citizen,
+    delegation: &mut VoteDelegation,
+) -> Result<u64> {
+    let vote_amt = delegation.vote_amt;
+    if delegation.forwarded_vote_amt > 0 {
+        return err!(PocketErrors::DelegationForwarded);
+    }
+    if delegate.free_delegated_voting_power(delegation.category)? < vote_amt {
+        return err!(PocketErrors::DelegatePendingVotes);
+    }
+
+    delegate.remove_delegated_voting_power(delegation.category, vote_amt)?;
+    citizen.max_pledged_voting_power = safe_sub(citizen.max_pledged_voting_power, vote_amt)?;
+    delegation.vote_amt = 0;
+    Ok(vote_amt)
+}
+
+// Splits remaining_accounts into the delegate's own (ProposalVote, Proposal) pairs and the
+// forwarded hop groups after them, see reclaim_forwarded
+pub fn split_unwind_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], &'a [AccountInfo<'info>]) {
+    let first_hop = remaining_accounts
+        .iter()
+        .position(is_delegation_path)
+        .unwrap_or(remaining_accounts.len());
+    remaining_accounts.split_at(first_hop)
+}
+
+fn is_delegation_path(info: &AccountInfo) -> bool {
+    Account::<DelegationPath>::try_from(info).is_ok()
+}
+
+struct ForwardedHop<'a, 'info> {
+    hop: Account<'info, DelegationPath>,
+    payer: &'a AccountInfo<'info>,
+    delegate: usize, // Index into the loaded citizens, the same citizen can sit on several hops
+    votes: &'a [AccountInfo<'info>],
+    parent: Option<usize>, // None is the root VoteDelegation
+    pull: u64,
+    from_below: u64,
+}
+
+// Pulls `amount` of the power the delegate forwarded on back out of the hops under `delegation`,
+// taking from each hop's unforwarded part before going further down and unwinding hop delegates'
+// votes where it's pledged. An emptied hop is closed and its rent goes back to whoever paid for it.
+// hop_accounts are groups of [DelegationPath, its payer, delegate Citizen, delegate
+// VotingPowerCheckpoints, (ProposalVote, Proposal) pairs of that delegate...], parents before children
+pub fn reclaim_forwarded<'a, 'info>(
+    delegation_key: Pubkey,
+    delegation: &mut VoteDelegation,
+    delegate: &mut Citizen,
+    amount: u64,
+    hop_accounts: &'a [AccountInfo<'info>],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let mut citizens: Vec<Account<'info, Citizen>> = vec![];
+    let mut checkpoints: Vec<Account<'info, VotingPowerCheckpoints>> = vec![];
+    let mut hops: Vec<ForwardedHop<'a, 'info>> = vec![];
+    let mut i = 0;
+    while i < hop_accounts.len() {
+        if i + 4 > hop_accounts.len() {
+            return err!(PocketErrors::InvalidUnwindAccounts);
+        }
+        let hop: Account<DelegationPath> = Account::try_from(&hop_accounts[i])
+            .map_err(|_| error!(PocketErrors::InvalidUnwindAccounts))?;
+        let payer = &hop_accounts[i + 1];
+        let delegate_info = &hop_accounts[i + 2];
+        let checkpoints_info = &hop_accounts[i + 3];
+
+        let parent = if hop.parent == delegation_key {
+            None
+        } else {
+            match hops.iter().position(|h| h.hop.key() == hop.parent) {
+                Some(p) => Some(p),
+                None => return err!(PocketErrors::InvalidUnwindAccounts),
+            }
+        };
+        if payer.key() != hop.payer
+            || delegate_info.key() != hop.delegate
+            || hops.iter().any(|h| h.hop.key() == hop.key())
+        {
+            return err!(PocketErrors::InvalidUnwindAccounts);
+        }
+
+        let delegate_index = match citizens.iter().position(|c| c.key() == hop.delegate) {
+            Some(c) => c,
+            None => {
+                let citizen: Account<Citizen> = Account::try_from(delegate_info)?;
+                let history: Account<VotingPowerCheckpoints> = Account::try_from(checkpoints_info)?;
+                if history.citizen != citizen.key() {
+                    return err!(PocketErrors::InvalidUnwindAccounts);
+                }
+                citizens.push(citizen);
+                checkpoints.push(history);
+                citizens.len() - 1
+            }
+        };
+
+        let mut end = i + 4;
+        while end < hop_accounts.len() && !is_delegation_path(&hop_accounts[end]) {
+            end += 1;
+        }
+        hops.push(ForwardedHop {
+            hop,
+            payer,
+            delegate: delegate_index,
+            votes: &hop_accounts[i + 4..end],
+            parent,
+            pull: 0,
+            from_below: 0,
+        });
+        i = end;
+    }
+
+    // Work out how much comes out of each hop, whatever a hop can't cover comes from its children
+    let mut root_need = amount;
+    let mut needs: Vec<u64> = vec![0; hops.len()];
+    for h in 0..hops.len() {
+        let need = match hops[h].parent {
+            Some(p) => &mut needs[p],
+            None => &mut root_need,
+        };
+        let pull = (*need).min(hops[h].hop.vote_amt);
+        *need -= pull;
+        let unforwarded = safe_sub(hops[h].hop.vote_amt, hops[h].hop.forwarded_vote_amt)?;
+        hops[h].pull = pull;
+        hops[h].from_below = pull - pull.min(unforwarded);
+        needs[h] = hops[h].from_below;
+    }
+    if root_need != 0 || needs.iter().any(|n| *n != 0) {
+        return err!(PocketErrors::InvalidUnwindAccounts);
+    }
+
+    // Children first, so each delegate has their forwarded power back before giving theirs up
+    for forwarded in hops.iter_mut().rev() {
+        if forwarded.pull == 0 {
+            continue;
+        }
+        let hop_delegate = &mut citizens[forwarded.delegate];
+        let hop_delegate_key = hop_delegate.key();
+        hop_delegate.max_pledged_voting_power =
+            safe_sub(hop_delegate.max_pledged_voting_power, forwarded.from_below)?;
+        forwarded.hop.forwarded_vote_amt =
+            safe_sub(forwarded.hop.forwarded_vote_amt, forwarded.from_below)?;
+
+        unwind_delegate_votes(
+            hop_delegate,
+            hop_delegate_key,
+            ProposalCategory::General,
+            forwarded.pull,
+            forwarded.votes,
+        )?;
+        if hop_delegate.free_voting_power()? < forwarded.pull {
+            return err!(PocketErrors::DelegatePendingVotes);
+        }
+        hop_delegate.remove_delegated_voting_power(ProposalCategory::General, forwarded.pull)?;
+
+        forwarded.hop.vote_amt = safe_sub(forwarded.hop.vote_amt, forwarded.pull)?;
+        if forwarded.hop.vote_amt == 0 {
+            forwarded.hop.close(forwarded.payer.clone())?;
+        } else {
+            forwarded.hop.exit(&crate::ID)?;
+        }
+    }
+
+    for (citizen, history) in citizens.iter_mut().zip(checkpoints.iter_mut()) {
+        history.record(citizen.total_voting_power)?;
+        citizen.exit(&crate::ID)?;
+        history.exit(&crate::ID)?;
+    }
+
+    delegation.forwarded_vote_amt = safe_sub(delegation.forwarded_vote_amt, amount)?;
+    delegate.max_pledged_voting_power = safe_sub(delegate.max_pledged_voting_power, amount)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::tests::citizen;
+
+    #[test]
+    fn unwind_needs_every_vote_of_the_delegate() {
+        let mut delegate = citizen();
+        delegate.delegated_voting_power = 10;
+        delegate.total_voting_power = 10;
+        delegate.max_pledged_voting_power = 10;
+        delegate.open_vote(ProposalCategory::General).unwrap();
+
+        let result = unwind_delegate_votes(
+            &mut delegate,
+            Pubkey::new_unique(),
+            ProposalCategory::General,
+            10,
+            &[],
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            error!(PocketErrors::MissingUnwindVotes)
+        );
+    }
+
+    #[test]
+    fn unwind_skips_votes_when_power_is_free() {
+        let mut delegate = citizen();
+        delegate.delegated_voting_power = 10;
+        delegate.total_voting_power = 10;
+        delegate.open_vote(ProposalCategory::General).unwrap();
+
+        let result = unwind_delegate_votes(
+            &mut delegate,
+            Pubkey::new_unique(),
+            ProposalCategory::General,
+            10,
+            &[],
+        );
+        assert_eq!(result.unwrap(), 0);
+    }
+}
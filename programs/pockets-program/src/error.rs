@@ -21,4 +21,180 @@ pub enum PocketErrors {
 
     #[msg("Faction doesn't have that many unallocated votes")]
     TransferFromFactionErrror,
+
+    #[msg("Signer is not the pending config authority")]
+    NotPendingAuthority,
+
+    #[msg("Proposals need between 2 and 5 options with labels of at most 16 characters")]
+    InvalidProposalOptions,
+
+    #[msg("That option doesn't exist on this proposal")]
+    InvalidProposalOption,
+
+    #[msg("Voting window must end after it starts and after the current time")]
+    InvalidVotingWindow,
+
+    #[msg("Proposal isn't accepting votes right now")]
+    VotingClosed,
+
+    #[msg("Proposal is still in its voting window")]
+    VotingStillOpen,
+
+    #[msg("Proposal has to be finalized first")]
+    ProposalNotFinalized,
+
+    #[msg("Instructions can only be attached before voting starts")]
+    VotingAlreadyStarted,
+
+    #[msg("Proposal hasn't passed")]
+    ProposalNotPassed,
+
+    #[msg("Proposal instructions were already executed")]
+    ProposalAlreadyExecuted,
+
+    #[msg("Proposal timelock hasn't elapsed yet")]
+    TimelockNotElapsed,
+
+    #[msg("Resource Field hasn't been developed yet!")]
+    ResourceFieldNotDeveloped,
+
+    #[msg("Resource Field hasn't refreshed yet")]
+    ResourceFieldNotReady,
+
+    #[msg("Only the citizen that developed the Resource Field can harvest it")]
+    NotResourceFieldClaimant,
+
+    #[msg("Not a known resource")]
+    InvalidResource,
+
+    #[msg("Pool mints have to be two different resources, ordered mint_a < mint_b")]
+    InvalidPoolMints,
+
+    #[msg("Pool fees are too high")]
+    InvalidPoolFee,
+
+    #[msg("Swap or liquidity change is worse than the provided limit")]
+    SlippageExceeded,
+
+    #[msg("Not enough liquidity in the pool")]
+    InsufficientLiquidity,
+
+    #[msg("First deposit has to mint more than MINIMUM_LIQUIDITY LP")]
+    InsufficientInitialLiquidity,
+
+    #[msg("Treasury token account is missing or doesn't belong to the pool's treasury")]
+    InvalidTreasuryAccount,
+
+    #[msg("Math overflow")]
+    Overflow,
+
+    #[msg("Math underflow")]
+    Underflow,
+
+    #[msg("Citizen has more voting power pledged than they hold")]
+    PledgeExceedsTotal,
+
+    #[msg("Voting power history for the proposal snapshot has been overwritten")]
+    SnapshotUnavailable,
+
+    #[msg("Vote exceeds the citizen's voting power at the proposal snapshot")]
+    ExceedsSnapshotVotingPower,
+
+    #[msg("Voting power history is full of checkpoints that are too recent to overwrite")]
+    CheckpointHistoryFull,
+
+    #[msg("Lockup was refreshed too recently")]
+    LockupRefreshTooSoon,
+
+    #[msg("Faction already has a governance mint")]
+    GovernanceMintAlreadySet,
+
+    #[msg("Mint is not the faction's governance mint")]
+    InvalidGovernanceMint,
+
+    #[msg("Citizen still has governance tokens deposited")]
+    GovernanceTokensDeposited,
+
+    #[msg("Lockup duration is invalid")]
+    InvalidLockupDuration,
+
+    #[msg("Voting power is still locked")]
+    LockupActive,
+
+    #[msg("Conviction parameters are invalid or conviction voting is disabled")]
+    InvalidConvictionParams,
+
+    #[msg("Proposal is not a conviction proposal")]
+    NotConvictionProposal,
+
+    #[msg("Quorum and approval must be between 0 and 10000 bps")]
+    InvalidThresholdParams,
+
+    #[msg("Proposal can't move to that status from its current one")]
+    IllegalTransition,
+
+    #[msg("Only the proposer can do this")]
+    NotProposer,
+
+    #[msg("Only the faction council can do this")]
+    NotCouncil,
+
+    #[msg("Proposal deposit and pledge were already settled")]
+    DepositAlreadySettled,
+
+    #[msg("Proposal deposit and pledge have to be settled first")]
+    DepositNotSettled,
+
+    #[msg("Citizen proposals aren't enabled for this faction")]
+    CitizenProposalsDisabled,
+
+    #[msg("Delegation amount must be more than 0")]
+    ZeroDelegationAmount,
+
+    #[msg("Timelock can't be negative")]
+    InvalidTimelock,
+
+    #[msg("Proposals can only call the System and Token programs")]
+    ProgramNotWhitelisted,
+
+    #[msg("Proposal title or uri is empty or too long")]
+    InvalidProposalMetadata,
+
+    #[msg("Remaining accounts must be the delegate's (ProposalVote, Proposal) pairs covering their pledged votes")]
+    InvalidUnwindAccounts,
+
+    #[msg(
+        "Every one of the delegate's votes that could hold the delegated power has to be passed"
+    )]
+    MissingUnwindVotes,
+
+    #[msg("Delegation expiry must be in the future")]
+    InvalidDelegationExpiry,
+
+    #[msg("Delegation hasn't expired")]
+    DelegationNotExpired,
+
+    #[msg("Forwarding would send delegated power back to a citizen it came from")]
+    DelegationCycle,
+
+    #[msg("Delegated power can't be forwarded that many times in this faction")]
+    DelegationTooDeep,
+
+    #[msg("Parent delegation wasn't delegated to this citizen")]
+    InvalidDelegationParent,
+
+    #[msg("Delegated power has been forwarded on and has to be returned first")]
+    DelegationForwarded,
+
+    #[msg("Only the server or the delegator can do this")]
+    NotDelegator,
+
+    #[msg("Citizen has to leave their current faction first")]
+    AlreadyInFaction,
+
+    #[msg("Faction still has members or open proposals")]
+    FactionNotEmpty,
+
+    #[msg("Remaining accounts must be (Citizen, VotingPowerCheckpoints) pairs of this faction's members")]
+    InvalidDisbandAccounts,
 }
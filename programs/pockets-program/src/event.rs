@@ -0,0 +1,317 @@
+use crate::account::*;
+use anchor_lang::prelude::*;
+
+// Faction
+#[event]
+pub struct FactionCreated {
+    pub faction: Pubkey,
+    pub id: String,
+    pub first_citizen: Pubkey,
+    pub starting_voting_power: u64,
+    pub threshold_to_pass: u64,
+}
+
+#[event]
+pub struct FactionUpdated {
+    pub faction: Pubkey,
+    pub old_max_voting_power: u64,
+    pub new_max_voting_power: u64,
+    pub old_threshold_to_pass: u64,
+    pub new_threshold_to_pass: u64,
+    pub unallocated_voting_power: u64,
+}
+
+#[event]
+pub struct FactionDeleted {
+    pub faction: Pubkey,
+}
+
+#[event]
+pub struct FactionDisbanding {
+    pub faction: Pubkey,
+    pub evicted: Vec<Pubkey>,
+    pub remaining_member_count: u64,
+    pub faction_unallocated_voting_power: u64,
+}
+
+#[event]
+pub struct FactionVotesTransferred {
+    pub faction: Pubkey,
+    pub citizen: Pubkey,
+    pub amount: u64,
+    pub faction_unallocated_voting_power: u64,
+    pub citizen_granted_voting_power: u64,
+    pub citizen_total_voting_power: u64,
+}
+
+#[event]
+pub struct GovernanceMintSet {
+    pub faction: Pubkey,
+    pub governance_mint: Pubkey,
+}
+
+#[event]
+pub struct VotingModeSet {
+    pub faction: Pubkey,
+    pub voting_mode: VotingMode,
+}
+
+#[event]
+pub struct ConvictionParamsSet {
+    pub faction: Pubkey,
+    pub half_life_seconds: i64,
+    pub spending_limit_bps: u64,
+    pub weight_bps: u64,
+}
+
+#[event]
+pub struct PassThresholdSet {
+    pub faction: Pubkey,
+    pub threshold_mode: ThresholdMode,
+    pub quorum_bps: u64,
+    pub approval_bps: u64,
+}
+
+#[event]
+pub struct CouncilSet {
+    pub faction: Pubkey,
+    pub council: Option<Pubkey>,
+}
+
+#[event]
+pub struct ProposalRequirementsSet {
+    pub faction: Pubkey,
+    pub min_voting_power: u64,
+    pub deposit: u64,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct LiquidDelegationDepthSet {
+    pub faction: Pubkey,
+    pub depth: u8,
+}
+
+// Citizen
+#[event]
+pub struct CitizenJoinedFaction {
+    pub citizen: Pubkey,
+    pub faction: Pubkey,
+}
+
+#[event]
+pub struct CitizenLeftFaction {
+    pub citizen: Pubkey,
+    pub faction: Pubkey,
+    pub returned_voting_power: u64,
+    pub faction_unallocated_voting_power: u64,
+}
+
+#[event]
+pub struct GovernanceTokensDeposited {
+    pub citizen: Pubkey,
+    pub faction: Pubkey,
+    pub amount: u64,
+    pub citizen_deposited_voting_power: u64,
+    pub citizen_total_voting_power: u64,
+    pub faction_max_voting_power: u64,
+}
+
+#[event]
+pub struct GovernanceTokensWithdrawn {
+    pub citizen: Pubkey,
+    pub faction: Pubkey,
+    pub amount: u64,
+    pub citizen_deposited_voting_power: u64,
+    pub citizen_total_voting_power: u64,
+    pub faction_max_voting_power: u64,
+}
+
+#[event]
+pub struct LockupUpdated {
+    pub lockup: Pubkey,
+    pub citizen: Pubkey,
+    pub amount: u64,
+    pub bonus_voting_power: u64,
+    pub end_ts: i64,
+    pub citizen_total_voting_power: u64,
+}
+
+#[event]
+pub struct LockupReleased {
+    pub lockup: Pubkey,
+    pub citizen: Pubkey,
+    pub amount: u64,
+    pub citizen_total_voting_power: u64,
+}
+
+// Proposal
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub faction: Pubkey,
+    pub id: String,
+    pub options: Vec<String>,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub title: String,
+    pub uri: String,
+    pub content_hash: [u8; 32],
+    pub category: ProposalCategory,
+}
+
+#[event]
+pub struct ProposalStatusChanged {
+    pub proposal: Pubkey,
+    pub old_status: ProposalStatus,
+    pub new_status: ProposalStatus,
+    pub winning_option: Option<u8>,
+    pub tallies: Vec<u64>,
+}
+
+#[event]
+pub struct ConvictionUpdated {
+    pub proposal: Pubkey,
+    pub staked: u64,
+    pub conviction: u64,
+    pub threshold: Option<u64>,
+}
+
+#[event]
+pub struct ProposalDepositSettled {
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub released_pledge: u64,
+    pub deposit: u64,
+    pub forfeited: bool,
+}
+
+// Votes
+#[event]
+pub struct VoteCast {
+    pub vote: Pubkey,
+    pub citizen: Pubkey,
+    pub proposal: Pubkey,
+    pub option: u8,
+    pub vote_amt: u64,
+    pub weight: u64,
+    pub option_tally: u64,
+    pub citizen_pledged_voting_power: u64,
+}
+
+#[event]
+pub struct VoteUpdated {
+    pub vote: Pubkey,
+    pub citizen: Pubkey,
+    pub proposal: Pubkey,
+    pub option: u8,
+    pub old_vote_amt: u64,
+    pub new_vote_amt: u64,
+    pub new_weight: u64,
+    pub option_tally: u64,
+    pub citizen_pledged_voting_power: u64,
+}
+
+#[event]
+pub struct VoteClosed {
+    pub vote: Pubkey,
+    pub citizen: Pubkey,
+    pub proposal: Pubkey,
+    pub option: u8,
+    pub vote_amt: u64,
+    pub removed_from_tally: bool,
+    pub option_tally: u64,
+    pub citizen_pledged_voting_power: u64,
+}
+
+#[event]
+pub struct VotesTransferred {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub vote_amt: u64,
+    pub from_total_voting_power: u64,
+    pub to_total_voting_power: u64,
+}
+
+// Delegation
+#[event]
+pub struct DelegationCreated {
+    pub delegation: Pubkey,
+    pub citizen: Pubkey,
+    pub delegate: Pubkey,
+    pub vote_amt: u64,
+    pub expires_at: Option<i64>,
+    pub category: ProposalCategory,
+    pub citizen_pledged_voting_power: u64,
+    pub delegate_total_voting_power: u64,
+}
+
+#[event]
+pub struct DelegationAdjusted {
+    pub delegation: Pubkey,
+    pub citizen: Pubkey,
+    pub delegate: Pubkey,
+    pub old_vote_amt: u64,
+    pub new_vote_amt: u64,
+    pub citizen_pledged_voting_power: u64,
+    pub delegate_total_voting_power: u64,
+}
+
+#[event]
+pub struct DelegationReturned {
+    pub delegation: Pubkey,
+    pub citizen: Pubkey,
+    pub delegate: Pubkey,
+    pub returned_vote_amt: u64,
+    pub remaining_vote_amt: u64,
+}
+
+#[event]
+pub struct DelegationRevoked {
+    pub delegation: Pubkey,
+    pub citizen: Pubkey,
+    pub delegate: Pubkey,
+    pub vote_amt: u64,
+    pub unwound_vote_amt: u64, // Pulled out of the delegate's votes
+    pub expired: bool,
+    pub delegate_total_voting_power: u64,
+}
+
+#[event]
+pub struct DelegationForwarded {
+    pub delegation_path: Pubkey,
+    pub parent: Pubkey,
+    pub citizen: Pubkey,
+    pub delegate: Pubkey,
+    pub vote_amt: u64,
+    pub path: Vec<Pubkey>,
+    pub delegate_total_voting_power: u64,
+}
+
+// Resource Fields
+#[event]
+pub struct ResourceFieldAllocated {
+    pub rf: Pubkey,
+    pub id: String,
+}
+
+#[event]
+pub struct ResourceFieldDeveloped {
+    pub rf: Pubkey,
+    pub citizen: Pubkey,
+    pub is_harvestable: bool,
+    pub times_developed: u64,
+    pub harvest: Option<Harvest>,
+    pub refresh_seconds: Option<u64>,
+}
+
+#[event]
+pub struct ResourceFieldHarvested {
+    pub rf: Pubkey,
+    pub citizen: Pubkey,
+    pub resource: String,
+    pub resource_mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub harvested_at: i64,
+}
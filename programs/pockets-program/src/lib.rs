@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::hash::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program;
+use anchor_spl::token;
 
 //declare_id!("GEUwNbnu9jkRMY8GX5Ar4R11mX9vXR8UDFnKZMn5uWLJ");
 declare_id!("9ejfcqQpWYQqXMiPeZVHhhbNu3qufuLWzGzVEBQYBEgS");
@@ -7,17 +11,46 @@ declare_id!("9ejfcqQpWYQqXMiPeZVHhhbNu3qufuLWzGzVEBQYBEgS");
 pub mod account;
 pub mod constant;
 pub mod context;
+pub mod delegation;
 pub mod error;
+pub mod event;
+pub mod math;
 
 use crate::account::*;
 use crate::constant::*;
 use crate::context::*;
+use crate::delegation::*;
 use crate::error::*;
+use crate::event::*;
+use crate::math::*;
 
 #[program]
 pub mod pockets_program {
     use super::*;
 
+    // Config
+    // Initialize Config (Upgrade Authority Only -- run once after deploy)
+    pub fn initialize_config(ctx: Context<InitializeConfig>, authority: Pubkey) -> Result<()> {
+        ctx.accounts.config.authority = authority;
+        ctx.accounts.config.pending_authority = None;
+        ctx.accounts.config.bump = *ctx.bumps.get("config").unwrap();
+        Ok(())
+    }
+
+    // Propose Authority (Authority Only)
+    // Two step handover, the new key has to accept before it takes over
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.config.pending_authority = Some(new_authority);
+        Ok(())
+    }
+
+    // Accept Authority (Pending Authority Only)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.config.authority = ctx.accounts.new_authority.key();
+        ctx.accounts.config.pending_authority = None;
+        Ok(())
+    }
+
     // Faction
     // Create Faction (Server Only)
     pub fn create_faction(
@@ -29,13 +62,89 @@ pub mod pockets_program {
         ctx.accounts.faction.id = id;
         ctx.accounts.faction.max_voting_power = starting_voting_power;
         ctx.accounts.faction.threshold_to_pass = threshold;
+        ctx.accounts.faction.governance_mint = None;
+        ctx.accounts.faction.voting_mode = VotingMode::Linear;
+        ctx.accounts.faction.conviction_half_life_seconds = 0;
+        ctx.accounts.faction.conviction_spending_limit_bps = 0;
+        ctx.accounts.faction.conviction_weight_bps = 0;
+        ctx.accounts.faction.threshold_mode = ThresholdMode::Absolute;
+        ctx.accounts.faction.quorum_bps = 0;
+        ctx.accounts.faction.approval_bps = 0;
+        ctx.accounts.faction.council = None;
+        ctx.accounts.faction.proposal_min_voting_power = 0;
+        ctx.accounts.faction.proposal_deposit = 0;
+        ctx.accounts.faction.citizen_proposals_enabled = false;
+        ctx.accounts.faction.liquid_delegation_depth = 0;
+        ctx.accounts.faction.member_count = 1; // first_citizen
+        ctx.accounts.faction.open_proposal_count = 0;
+        ctx.accounts.faction.bump = *ctx.bumps.get("faction").unwrap();
 
         ctx.accounts.first_citizen.faction = Some(ctx.accounts.faction.key());
         ctx.accounts.first_citizen.granted_voting_power = starting_voting_power;
         ctx.accounts.first_citizen.total_voting_power = starting_voting_power;
+        ctx.accounts
+            .first_citizen_checkpoints
+            .record(starting_voting_power)?;
+
+        emit!(FactionCreated {
+            faction: ctx.accounts.faction.key(),
+            id: ctx.accounts.faction.id.clone(),
+            first_citizen: ctx.accounts.first_citizen.key(),
+            starting_voting_power,
+            threshold_to_pass: threshold,
+        });
+        Ok(())
+    }
+    // Delete Faction (Server Only -- once every member is gone and no proposal is open)
+    pub fn delete_faction(ctx: Context<DeleteFactionAccount>) -> Result<()> {
+        if ctx.accounts.faction.member_count != 0 || ctx.accounts.faction.open_proposal_count != 0 {
+            return err!(PocketErrors::FactionNotEmpty);
+        }
+        emit!(FactionDeleted {
+            faction: ctx.accounts.faction.key(),
+        });
         Ok(())
     }
-    pub fn delete_faction(_ctx: Context<DeleteFactionAccount>) -> Result<()> {
+
+    // Disband Faction (Server Only)
+    // Evicts members in batches so the faction can be deleted, remaining_accounts are
+    // (Citizen, VotingPowerCheckpoints) pairs. Each citizen has to be clear the same way
+    // leave_faction requires
+    pub fn disband_faction<'info>(
+        ctx: Context<'_, '_, '_, 'info, DisbandFaction<'info>>,
+    ) -> Result<()> {
+        let faction_key = ctx.accounts.faction.key();
+        let mut evicted: Vec<Pubkey> = vec![];
+        for pair in ctx.remaining_accounts.chunks(2) {
+            if pair.len() != 2 {
+                return err!(PocketErrors::InvalidDisbandAccounts);
+            }
+            let mut citizen: Account<Citizen> = Account::try_from(&pair[0])?;
+            let mut checkpoints: Account<VotingPowerCheckpoints> = Account::try_from(&pair[1])?;
+            if citizen.faction != Some(faction_key) || checkpoints.citizen != citizen.key() {
+                return err!(PocketErrors::InvalidDisbandAccounts);
+            }
+
+            let returned_voting_power = ctx.accounts.faction.remove_member(&mut citizen)?;
+            checkpoints.record(0)?;
+            citizen.exit(&crate::ID)?;
+            checkpoints.exit(&crate::ID)?;
+
+            emit!(CitizenLeftFaction {
+                citizen: citizen.key(),
+                faction: faction_key,
+                returned_voting_power,
+                faction_unallocated_voting_power: ctx.accounts.faction.unallocated_voting_power,
+            });
+            evicted.push(citizen.key());
+        }
+
+        emit!(FactionDisbanding {
+            faction: faction_key,
+            evicted,
+            remaining_member_count: ctx.accounts.faction.member_count,
+            faction_unallocated_voting_power: ctx.accounts.faction.unallocated_voting_power,
+        });
         Ok(())
     }
 
@@ -45,13 +154,26 @@ pub mod pockets_program {
         max_voting_power: u64,
         threshold: u64,
     ) -> Result<()> {
+        let old_max_voting_power = ctx.accounts.faction.max_voting_power;
+        let old_threshold_to_pass = ctx.accounts.faction.threshold_to_pass;
         if max_voting_power > ctx.accounts.faction.max_voting_power {
-            ctx.accounts.faction.unallocated_voting_power +=
-                max_voting_power - ctx.accounts.faction.max_voting_power
+            ctx.accounts.faction.unallocated_voting_power = safe_add(
+                ctx.accounts.faction.unallocated_voting_power,
+                max_voting_power - ctx.accounts.faction.max_voting_power,
+            )?;
         }
 
         ctx.accounts.faction.max_voting_power = max_voting_power;
         ctx.accounts.faction.threshold_to_pass = threshold;
+
+        emit!(FactionUpdated {
+            faction: ctx.accounts.faction.key(),
+            old_max_voting_power,
+            new_max_voting_power: max_voting_power,
+            old_threshold_to_pass,
+            new_threshold_to_pass: threshold,
+            unallocated_voting_power: ctx.accounts.faction.unallocated_voting_power,
+        });
         Ok(())
     }
 
@@ -59,13 +181,29 @@ pub mod pockets_program {
         ctx: Context<TransferFromFaction>,
         amount: u64,
     ) -> Result<()> {
-        if amount > ctx.accounts.faction.max_voting_power {
+        if amount > ctx.accounts.faction.unallocated_voting_power {
             return err!(PocketErrors::TransferFromFactionErrror);
         }
 
-        ctx.accounts.faction.unallocated_voting_power -= amount;
-        ctx.accounts.citizen.granted_voting_power += amount;
-        ctx.accounts.citizen.total_voting_power += amount;
+        ctx.accounts.faction.unallocated_voting_power =
+            safe_sub(ctx.accounts.faction.unallocated_voting_power, amount)?;
+        ctx.accounts.citizen.granted_voting_power =
+            safe_add(ctx.accounts.citizen.granted_voting_power, amount)?;
+        ctx.accounts.citizen.total_voting_power =
+            safe_add(ctx.accounts.citizen.total_voting_power, amount)?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        emit!(FactionVotesTransferred {
+            faction: ctx.accounts.faction.key(),
+            citizen: ctx.accounts.citizen.key(),
+            amount,
+            faction_unallocated_voting_power: ctx.accounts.faction.unallocated_voting_power,
+            citizen_granted_voting_power: ctx.accounts.citizen.granted_voting_power,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+        });
         Ok(())
     }
 
@@ -77,6 +215,19 @@ pub mod pockets_program {
         ctx.accounts.citizen.granted_voting_power = 0;
         ctx.accounts.citizen.total_voting_power = 0;
         ctx.accounts.citizen.max_pledged_voting_power = 0;
+        ctx.accounts.citizen.deposited_voting_power = 0;
+        ctx.accounts.citizen.locked_voting_power = 0;
+        ctx.accounts.citizen.topic_delegated_voting_power = [0; TOPIC_CATEGORIES];
+        ctx.accounts.citizen.topic_pledged_voting_power = [0; TOPIC_CATEGORIES];
+        ctx.accounts.citizen.vote_count = 0;
+        ctx.accounts.citizen.topic_vote_count = [0; TOPIC_CATEGORIES];
+        ctx.accounts.citizen.bump = *ctx.bumps.get("citizen").unwrap();
+
+        ctx.accounts.citizen_checkpoints.citizen = ctx.accounts.citizen.key();
+        ctx.accounts.citizen_checkpoints.head = 0;
+        ctx.accounts.citizen_checkpoints.checkpoints = vec![];
+        ctx.accounts.citizen_checkpoints.bump = *ctx.bumps.get("citizen_checkpoints").unwrap();
+        ctx.accounts.citizen_checkpoints.record(0)?;
         Ok(())
     }
 
@@ -86,7 +237,16 @@ pub mod pockets_program {
 
     // Join Faction (Server Only - Need to Record in DB)
     pub fn join_faction(ctx: Context<JoinFaction>) -> Result<()> {
+        if ctx.accounts.citizen.faction.is_some() {
+            return err!(PocketErrors::AlreadyInFaction);
+        }
         ctx.accounts.citizen.faction = Some(ctx.accounts.faction.key());
+        ctx.accounts.faction.member_count = safe_add(ctx.accounts.faction.member_count, 1)?;
+
+        emit!(CitizenJoinedFaction {
+            citizen: ctx.accounts.citizen.key(),
+            faction: ctx.accounts.faction.key(),
+        });
         Ok(())
     }
 
@@ -97,97 +257,1035 @@ pub mod pockets_program {
      *  2. This user has 0 voting power delegated by other users
      *  3. This user has 0 voting power locked up in proposals
      *
+     *  4. This user has 0 governance tokens deposited
+     *  5. This user has no Lockup
+     *
      * User should close the above accounts before leaving a faction
      */
     pub fn leave_faction(ctx: Context<LeaveFaction>) -> Result<()> {
-        if ctx.accounts.citizen.max_pledged_voting_power != 0
-            || ctx.accounts.citizen.delegated_voting_power != 0
+        let returned_voting_power = ctx
+            .accounts
+            .faction
+            .remove_member(&mut ctx.accounts.citizen)?;
+        ctx.accounts.citizen_checkpoints.record(0)?;
+
+        emit!(CitizenLeftFaction {
+            citizen: ctx.accounts.citizen.key(),
+            faction: ctx.accounts.faction.key(),
+            returned_voting_power,
+            faction_unallocated_voting_power: ctx.accounts.faction.unallocated_voting_power,
+        });
+        Ok(())
+    }
+
+    // Set Voting Mode (Server Only -- proposals keep the mode they were created with)
+    pub fn set_voting_mode(ctx: Context<ConfigureFaction>, voting_mode: VotingMode) -> Result<()> {
+        ctx.accounts.faction.voting_mode = voting_mode;
+
+        emit!(VotingModeSet {
+            faction: ctx.accounts.faction.key(),
+            voting_mode,
+        });
+        Ok(())
+    }
+
+    // Set Council (Server Only)
+    pub fn set_council(ctx: Context<ConfigureFaction>, council: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.faction.council = council;
+
+        emit!(CouncilSet {
+            faction: ctx.accounts.faction.key(),
+            council,
+        });
+        Ok(())
+    }
+
+    // Set Proposal Requirements (Server Only)
+    // What citizens put up to create proposals without the server, off for new factions
+    pub fn set_proposal_requirements(
+        ctx: Context<ConfigureFaction>,
+        min_voting_power: u64,
+        deposit: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.faction.proposal_min_voting_power = min_voting_power;
+        ctx.accounts.faction.proposal_deposit = deposit;
+        ctx.accounts.faction.citizen_proposals_enabled = enabled;
+
+        emit!(ProposalRequirementsSet {
+            faction: ctx.accounts.faction.key(),
+            min_voting_power,
+            deposit,
+            enabled,
+        });
+        Ok(())
+    }
+
+    // Set Liquid Delegation Depth (Server Only)
+    pub fn set_liquid_delegation_depth(ctx: Context<ConfigureFaction>, depth: u8) -> Result<()> {
+        if depth > MAX_DELEGATION_DEPTH {
+            return err!(PocketErrors::DelegationTooDeep);
+        }
+        ctx.accounts.faction.liquid_delegation_depth = depth;
+
+        emit!(LiquidDelegationDepthSet {
+            faction: ctx.accounts.faction.key(),
+            depth,
+        });
+        Ok(())
+    }
+
+    // Set Pass Threshold (Server Only)
+    // Percentage mode scales with supply, Absolute keeps using threshold_to_pass
+    pub fn set_pass_threshold(
+        ctx: Context<ConfigureFaction>,
+        threshold_mode: ThresholdMode,
+        quorum_bps: u64,
+        approval_bps: u64,
+    ) -> Result<()> {
+        if quorum_bps > BPS_DENOMINATOR || approval_bps > BPS_DENOMINATOR {
+            return err!(PocketErrors::InvalidThresholdParams);
+        }
+
+        ctx.accounts.faction.threshold_mode = threshold_mode;
+        ctx.accounts.faction.quorum_bps = quorum_bps;
+        ctx.accounts.faction.approval_bps = approval_bps;
+
+        emit!(PassThresholdSet {
+            faction: ctx.accounts.faction.key(),
+            threshold_mode,
+            quorum_bps,
+            approval_bps,
+        });
+        Ok(())
+    }
+
+    // Set Conviction Params (Server Only)
+    pub fn set_conviction_params(
+        ctx: Context<ConfigureFaction>,
+        half_life_seconds: i64,
+        spending_limit_bps: u64,
+        weight_bps: u64,
+    ) -> Result<()> {
+        if half_life_seconds <= 0 || spending_limit_bps == 0 || spending_limit_bps > BPS_DENOMINATOR
         {
-            return err!(PocketErrors::CitizenHasOutstandingVotes);
-        } else {
-            ctx.accounts.faction.unallocated_voting_power +=
-                ctx.accounts.citizen.granted_voting_power;
+            return err!(PocketErrors::InvalidConvictionParams);
+        }
+
+        ctx.accounts.faction.conviction_half_life_seconds = half_life_seconds;
+        ctx.accounts.faction.conviction_spending_limit_bps = spending_limit_bps;
+        ctx.accounts.faction.conviction_weight_bps = weight_bps;
+
+        emit!(ConvictionParamsSet {
+            faction: ctx.accounts.faction.key(),
+            half_life_seconds,
+            spending_limit_bps,
+            weight_bps,
+        });
+        Ok(())
+    }
+
+    // Governance Tokens
+    // Set Governance Mint (Server Only -- can't be changed once set)
+    pub fn set_governance_mint(ctx: Context<SetGovernanceMint>) -> Result<()> {
+        ctx.accounts.faction.governance_mint = Some(ctx.accounts.governance_mint.key());
+
+        emit!(GovernanceMintSet {
+            faction: ctx.accounts.faction.key(),
+            governance_mint: ctx.accounts.governance_mint.key(),
+        });
+        Ok(())
+    }
+
+    // Create Governance Vault (Citizen of the Faction)
+    pub fn create_governance_vault(_ctx: Context<CreateGovernanceVault>) -> Result<()> {
+        Ok(())
+    }
+
+    // Deposit Governance Tokens
+    // Each token deposited is granted as one vote
+    pub fn deposit_governance_tokens(
+        ctx: Context<DepositGovernanceTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.from.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.wallet.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        ctx.accounts.citizen.deposited_voting_power =
+            safe_add(ctx.accounts.citizen.deposited_voting_power, amount)?;
+        ctx.accounts.citizen.granted_voting_power =
+            safe_add(ctx.accounts.citizen.granted_voting_power, amount)?;
+        ctx.accounts.citizen.total_voting_power =
+            safe_add(ctx.accounts.citizen.total_voting_power, amount)?;
+        ctx.accounts.faction.max_voting_power =
+            safe_add(ctx.accounts.faction.max_voting_power, amount)?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        emit!(GovernanceTokensDeposited {
+            citizen: ctx.accounts.citizen.key(),
+            faction: ctx.accounts.faction.key(),
+            amount,
+            citizen_deposited_voting_power: ctx.accounts.citizen.deposited_voting_power,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+            faction_max_voting_power: ctx.accounts.faction.max_voting_power,
+        });
+        Ok(())
+    }
 
-            ctx.accounts.citizen.faction = None;
-            ctx.accounts.citizen.delegated_voting_power = 0;
-            ctx.accounts.citizen.granted_voting_power = 0;
-            ctx.accounts.citizen.total_voting_power = 0;
+    // Withdraw Governance Tokens
+    // Can't pull out power that's pledged to proposals, delegated away or locked up
+    pub fn withdraw_governance_tokens(
+        ctx: Context<WithdrawGovernanceTokens>,
+        amount: u64,
+    ) -> Result<()> {
+        if ctx.accounts.citizen.deposited_voting_power < amount
+            || ctx.accounts.citizen.unlocked_voting_power()? < amount
+        {
+            return err!(PocketErrors::InvalidVotingPowerDecrement);
+        }
+
+        ctx.accounts.citizen.deposited_voting_power =
+            safe_sub(ctx.accounts.citizen.deposited_voting_power, amount)?;
+        ctx.accounts.citizen.granted_voting_power =
+            safe_sub(ctx.accounts.citizen.granted_voting_power, amount)?;
+        ctx.accounts.citizen.total_voting_power =
+            safe_sub(ctx.accounts.citizen.total_voting_power, amount)?;
+        ctx.accounts.faction.max_voting_power =
+            safe_sub(ctx.accounts.faction.max_voting_power, amount)?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        let citizen_mint = ctx.accounts.citizen.mint;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEEDS_CITIZEN,
+            citizen_mint.as_ref(),
+            &[ctx.accounts.citizen.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.to.to_account_info(),
+                    authority: ctx.accounts.citizen.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(GovernanceTokensWithdrawn {
+            citizen: ctx.accounts.citizen.key(),
+            faction: ctx.accounts.faction.key(),
+            amount,
+            citizen_deposited_voting_power: ctx.accounts.citizen.deposited_voting_power,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+            faction_max_voting_power: ctx.accounts.faction.max_voting_power,
+        });
+        Ok(())
+    }
+
+    // Lockups
+    // Create Lockup
+    // Locks granted voting power (including deposited tokens) for up to MAX_LOCKUP_SECONDS
+    pub fn create_lockup(
+        ctx: Context<CreateLockup>,
+        amount: u64,
+        duration_seconds: i64,
+    ) -> Result<()> {
+        if duration_seconds <= 0 || duration_seconds > MAX_LOCKUP_SECONDS {
+            return err!(PocketErrors::InvalidLockupDuration);
         }
+        if safe_sub(
+            ctx.accounts.citizen.granted_voting_power,
+            ctx.accounts.citizen.locked_voting_power,
+        )? < amount
+        {
+            return err!(PocketErrors::CitizenLacksVotingPower);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.lockup.citizen = ctx.accounts.citizen.key();
+        ctx.accounts.lockup.amount = amount;
+        ctx.accounts.lockup.bonus_voting_power = 0;
+        ctx.accounts.lockup.start_ts = now;
+        ctx.accounts.lockup.end_ts = now + duration_seconds;
+        ctx.accounts.lockup.bump = *ctx.bumps.get("lockup").unwrap();
+
+        ctx.accounts.citizen.locked_voting_power =
+            safe_add(ctx.accounts.citizen.locked_voting_power, amount)?;
+        let bonus = ctx.accounts.lockup.bonus_at(now)?;
+        ctx.accounts
+            .lockup
+            .apply_bonus(&mut ctx.accounts.citizen, bonus)?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        emit!(LockupUpdated {
+            lockup: ctx.accounts.lockup.key(),
+            citizen: ctx.accounts.citizen.key(),
+            amount,
+            bonus_voting_power: ctx.accounts.lockup.bonus_voting_power,
+            end_ts: ctx.accounts.lockup.end_ts,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+        });
         Ok(())
     }
 
+    // Extend Lockup
+    // Can push out the unlock and/or lock more power, never shorten
+    pub fn extend_lockup(
+        ctx: Context<ExtendLockup>,
+        additional_amount: u64,
+        new_end_ts: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if new_end_ts < ctx.accounts.lockup.end_ts
+            || new_end_ts <= now
+            || new_end_ts - now > MAX_LOCKUP_SECONDS
+        {
+            return err!(PocketErrors::InvalidLockupDuration);
+        }
+        if safe_sub(
+            ctx.accounts.citizen.granted_voting_power,
+            ctx.accounts.citizen.locked_voting_power,
+        )? < additional_amount
+        {
+            return err!(PocketErrors::CitizenLacksVotingPower);
+        }
+
+        ctx.accounts.lockup.amount = safe_add(ctx.accounts.lockup.amount, additional_amount)?;
+        ctx.accounts.lockup.end_ts = new_end_ts;
+        ctx.accounts.citizen.locked_voting_power =
+            safe_add(ctx.accounts.citizen.locked_voting_power, additional_amount)?;
+        let bonus = ctx.accounts.lockup.bonus_at(now)?;
+        ctx.accounts
+            .lockup
+            .apply_bonus(&mut ctx.accounts.citizen, bonus)?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        emit!(LockupUpdated {
+            lockup: ctx.accounts.lockup.key(),
+            citizen: ctx.accounts.citizen.key(),
+            amount: ctx.accounts.lockup.amount,
+            bonus_voting_power: ctx.accounts.lockup.bonus_voting_power,
+            end_ts: ctx.accounts.lockup.end_ts,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+        });
+        Ok(())
+    }
+
+    // Refresh Lockup (Permissionless crank)
+    // Decays the bonus, but never below what the citizen currently has pledged
+    // At most once per CHECKPOINT_INTERVAL_SLOTS, so cranking can't fill the checkpoint history
+    pub fn refresh_lockup(ctx: Context<RefreshLockup>) -> Result<()> {
+        let clock = Clock::get()?;
+        if let Some(latest_slot) = ctx.accounts.citizen_checkpoints.latest_slot() {
+            if clock.slot.saturating_sub(latest_slot) < CHECKPOINT_INTERVAL_SLOTS {
+                return err!(PocketErrors::LockupRefreshTooSoon);
+            }
+        }
+        let now = clock.unix_timestamp;
+        let free_voting_power = ctx.accounts.citizen.free_voting_power()?;
+        let bonus = ctx.accounts.lockup.bonus_at(now)?.max(
+            ctx.accounts
+                .lockup
+                .bonus_voting_power
+                .saturating_sub(free_voting_power),
+        );
+        ctx.accounts
+            .lockup
+            .apply_bonus(&mut ctx.accounts.citizen, bonus)?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        emit!(LockupUpdated {
+            lockup: ctx.accounts.lockup.key(),
+            citizen: ctx.accounts.citizen.key(),
+            amount: ctx.accounts.lockup.amount,
+            bonus_voting_power: ctx.accounts.lockup.bonus_voting_power,
+            end_ts: ctx.accounts.lockup.end_ts,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+        });
+        Ok(())
+    }
+
+    // Unlock
+    // Only after end_ts, any leftover bonus must not be pledged
+    pub fn unlock(ctx: Context<Unlock>) -> Result<()> {
+        if Clock::get()?.unix_timestamp < ctx.accounts.lockup.end_ts {
+            return err!(PocketErrors::LockupActive);
+        }
+
+        ctx.accounts
+            .lockup
+            .apply_bonus(&mut ctx.accounts.citizen, 0)?;
+        ctx.accounts.citizen.locked_voting_power = safe_sub(
+            ctx.accounts.citizen.locked_voting_power,
+            ctx.accounts.lockup.amount,
+        )?;
+        let total_voting_power = ctx.accounts.citizen.total_voting_power;
+        ctx.accounts
+            .citizen_checkpoints
+            .record(total_voting_power)?;
+
+        emit!(LockupReleased {
+            lockup: ctx.accounts.lockup.key(),
+            citizen: ctx.accounts.citizen.key(),
+            amount: ctx.accounts.lockup.amount,
+            citizen_total_voting_power: ctx.accounts.citizen.total_voting_power,
+        });
+        Ok(())
+    }
+
+    // Treasury
+    // Deposit SOL to Treasury (Any Citizen of the Faction)
+    pub fn deposit_to_treasury(ctx: Context<DepositToTreasury>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.wallet.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    // Deposit SPL Tokens to Treasury (Any Citizen of the Faction)
+    pub fn deposit_tokens_to_treasury(
+        ctx: Context<DepositTokensToTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.from.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.wallet.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    // Withdraw SOL from Treasury (Faction PDA Only -- run through execute_proposal)
+    pub fn withdraw_from_treasury(ctx: Context<WithdrawFromTreasury>, amount: u64) -> Result<()> {
+        let faction_key = ctx.accounts.faction.key();
+        let treasury_bump = *ctx.bumps.get("treasury").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[SEEDS_FACTION, faction_key.as_ref(), &[treasury_bump]]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.recipient.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
+    // Withdraw SPL Tokens from Treasury (Faction PDA Only -- run through execute_proposal)
+    pub fn withdraw_tokens_from_treasury(
+        ctx: Context<WithdrawTokensFromTreasury>,
+        amount: u64,
+    ) -> Result<()> {
+        let faction_key = ctx.accounts.faction.key();
+        let treasury_bump = *ctx.bumps.get("treasury").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[SEEDS_FACTION, faction_key.as_ref(), &[treasury_bump]]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    to: ctx.accounts.recipient.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+
     // Proposals
+    // DRAFT -> VOTING -> PASSED/FAILED, PASSED -> EXECUTED
+    // Proposer can cancel before voting ends, the council can veto until it's executed
     // Create Proposal (Server Only - Need to Validate on Server and provide ID)
-    pub fn create_proposal(ctx: Context<CreateProposal>, id: String) -> Result<()> {
-        ctx.accounts.proposal.id = id;
-        ctx.accounts.proposal.status = ProposalStatus::VOTING;
-        ctx.accounts.proposal.faction = ctx.accounts.faction.key();
+    // Options default to For/Against/Abstain if none are provided
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        id: String,
+        options: Option<Vec<String>>,
+        start_ts: i64,
+        end_ts: i64,
+        metadata: ProposalMetadata,
+        category: ProposalCategory,
+    ) -> Result<()> {
+        let proposal_key = ctx.accounts.proposal.key();
+        ctx.accounts.proposal.init(
+            proposal_key,
+            &mut ctx.accounts.faction,
+            NewProposal {
+                id,
+                proposer: ctx.accounts.proposer.key(),
+                options,
+                start_ts,
+                end_ts,
+                metadata,
+                category,
+                proposal_type: ProposalType::Standard,
+                requested_amount: 0,
+                proposer_pledge: 0,
+                deposit: 0,
+            },
+            *ctx.bumps.get("proposal").unwrap(),
+        )
+    }
+
+    // Create Citizen Proposal (Any Citizen of the Faction)
+    // Pledges the faction's proposal_min_voting_power and escrows proposal_deposit lamports
+    // in the proposal account until settle_proposal_deposit
+    pub fn create_citizen_proposal(
+        ctx: Context<CreateCitizenProposal>,
+        id: String,
+        options: Option<Vec<String>>,
+        start_ts: i64,
+        end_ts: i64,
+        metadata: ProposalMetadata,
+        category: ProposalCategory,
+    ) -> Result<()> {
+        if !ctx.accounts.faction.citizen_proposals_enabled {
+            return err!(PocketErrors::CitizenProposalsDisabled);
+        }
+        let pledge = ctx.accounts.faction.proposal_min_voting_power;
+        if ctx.accounts.proposer.free_voting_power()? < pledge {
+            return err!(PocketErrors::CitizenLacksVotingPower);
+        }
+        ctx.accounts.proposer.max_pledged_voting_power =
+            safe_add(ctx.accounts.proposer.max_pledged_voting_power, pledge)?;
+
+        let deposit = ctx.accounts.faction.proposal_deposit;
+        if deposit > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.wallet.to_account_info(),
+                        to: ctx.accounts.proposal.to_account_info(),
+                    },
+                ),
+                deposit,
+            )?;
+        }
+
+        let proposal_key = ctx.accounts.proposal.key();
+        ctx.accounts.proposal.init(
+            proposal_key,
+            &mut ctx.accounts.faction,
+            NewProposal {
+                id,
+                proposer: ctx.accounts.proposer.key(),
+                options,
+                start_ts,
+                end_ts,
+                metadata,
+                category,
+                proposal_type: ProposalType::Standard,
+                requested_amount: 0,
+                proposer_pledge: pledge,
+                deposit,
+            },
+            *ctx.bumps.get("proposal").unwrap(),
+        )
+    }
+
+    // Settle Proposal Deposit (Permissionless -- once the proposal is done)
+    // Always releases the pledge, the deposit goes back to whoever holds the proposer
+    // citizen unless the proposal was vetoed or missed quorum, then it goes to the treasury
+    pub fn settle_proposal_deposit(ctx: Context<SettleProposalDeposit>) -> Result<()> {
+        match ctx.accounts.proposal.status {
+            ProposalStatus::DRAFT | ProposalStatus::VOTING => {
+                return err!(PocketErrors::ProposalNotFinalized)
+            }
+            _ => {}
+        }
+        let pledge = ctx.accounts.proposal.proposer_pledge;
+        let deposit = ctx.accounts.proposal.deposit;
+        if pledge == 0 && deposit == 0 {
+            return err!(PocketErrors::DepositAlreadySettled);
+        }
+
+        ctx.accounts.proposer.max_pledged_voting_power =
+            safe_sub(ctx.accounts.proposer.max_pledged_voting_power, pledge)?;
+
+        let forfeited = ctx
+            .accounts
+            .proposal
+            .forfeits_deposit(&ctx.accounts.faction);
+        let to = if forfeited {
+            ctx.accounts.treasury.to_account_info()
+        } else {
+            ctx.accounts.proposer_wallet.to_account_info()
+        };
+        let from = ctx.accounts.proposal.to_account_info();
+        **from.try_borrow_mut_lamports()? = safe_sub(from.lamports(), deposit)?;
+        **to.try_borrow_mut_lamports()? = safe_add(to.lamports(), deposit)?;
+
+        ctx.accounts.proposal.proposer_pledge = 0;
+        ctx.accounts.proposal.deposit = 0;
+
+        emit!(ProposalDepositSettled {
+            proposal: ctx.accounts.proposal.key(),
+            proposer: ctx.accounts.proposer.key(),
+            released_pledge: pledge,
+            deposit,
+            forfeited,
+        });
+        Ok(())
+    }
+
+    // Open Proposal (Proposer Only) -- DRAFT to VOTING
+    pub fn open_proposal(ctx: Context<OpenProposal>) -> Result<()> {
+        let old_status = ctx.accounts.proposal.transition(ProposalStatus::VOTING)?;
+
+        emit!(ProposalStatusChanged {
+            proposal: ctx.accounts.proposal.key(),
+            old_status,
+            new_status: ctx.accounts.proposal.status.clone(),
+            winning_option: ctx.accounts.proposal.winning_option,
+            tallies: ctx.accounts.proposal.tallies.clone(),
+        });
+        Ok(())
+    }
+
+    // Create Conviction Proposal (Server Only)
+    // Citizens stake For votes, passes through update_conviction once conviction crosses
+    // a threshold that grows with the share of the treasury requested
+    pub fn create_conviction_proposal(
+        ctx: Context<CreateProposal>,
+        id: String,
+        requested_amount: u64,
+        start_ts: i64,
+        end_ts: i64,
+        metadata: ProposalMetadata,
+    ) -> Result<()> {
+        if ctx.accounts.faction.conviction_half_life_seconds <= 0 {
+            return err!(PocketErrors::InvalidConvictionParams);
+        }
+
+        let proposal_key = ctx.accounts.proposal.key();
+        ctx.accounts.proposal.init(
+            proposal_key,
+            &mut ctx.accounts.faction,
+            NewProposal {
+                id,
+                proposer: ctx.accounts.proposer.key(),
+                options: None,
+                start_ts,
+                end_ts,
+                metadata,
+                // Conviction proposals spend the treasury
+                category: ProposalCategory::Treasury,
+                proposal_type: ProposalType::Conviction,
+                requested_amount,
+                proposer_pledge: 0,
+                deposit: 0,
+            },
+            *ctx.bumps.get("proposal").unwrap(),
+        )
+    }
+
+    // Update Conviction (Permissionless crank)
+    // Moves the proposal to PASSED once conviction reaches the threshold
+    pub fn update_conviction(ctx: Context<UpdateConviction>) -> Result<()> {
+        if ctx.accounts.proposal.proposal_type != ProposalType::Conviction {
+            return err!(PocketErrors::NotConvictionProposal);
+        }
+        let now = Clock::get().unwrap().unix_timestamp;
+        if !ctx.accounts.proposal.is_voting_open(now) {
+            return err!(PocketErrors::VotingClosed);
+        }
+
+        ctx.accounts.proposal.accrue_conviction(now)?;
+        let threshold = ctx.accounts.proposal.conviction_threshold(
+            ctx.accounts.faction.max_voting_power,
+            ctx.accounts.treasury.lamports(),
+            ctx.accounts.faction.conviction_spending_limit_bps,
+            ctx.accounts.faction.conviction_weight_bps,
+        );
+
+        emit!(ConvictionUpdated {
+            proposal: ctx.accounts.proposal.key(),
+            staked: ctx.accounts.proposal.tallies[OPTION_FOR as usize],
+            conviction: ctx.accounts.proposal.conviction,
+            threshold,
+        });
+
+        if let Some(threshold) = threshold {
+            if ctx.accounts.proposal.conviction >= threshold {
+                let old_status = ctx.accounts.proposal.transition(ProposalStatus::PASSED)?;
+                ctx.accounts
+                    .faction
+                    .track_proposal(&old_status, &ctx.accounts.proposal.status)?;
+                ctx.accounts.proposal.winning_option = Some(OPTION_FOR);
+                ctx.accounts.proposal.finalized_at = Some(now);
+
+                emit!(ProposalStatusChanged {
+                    proposal: ctx.accounts.proposal.key(),
+                    old_status,
+                    new_status: ProposalStatus::PASSED,
+                    winning_option: ctx.accounts.proposal.winning_option,
+                    tallies: ctx.accounts.proposal.tallies.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Attach Proposal Instructions (Server Only - before voting starts)
+    pub fn create_proposal_instructions(
+        ctx: Context<CreateProposalInstructions>,
+        hold_up_seconds: i64,
+        instructions: Vec<InstructionData>,
+    ) -> Result<()> {
+        let clock = Clock::get().unwrap();
+        if clock.unix_timestamp >= ctx.accounts.proposal.start_ts {
+            return err!(PocketErrors::VotingAlreadyStarted);
+        }
+        if hold_up_seconds < 0 {
+            return err!(PocketErrors::InvalidTimelock);
+        }
+        if !instructions.iter().all(|ix| ix.is_whitelisted()) {
+            return err!(PocketErrors::ProgramNotWhitelisted);
+        }
+
+        ctx.accounts.proposal_instruction.proposal = ctx.accounts.proposal.key();
+        ctx.accounts.proposal_instruction.hold_up_seconds = hold_up_seconds;
+        ctx.accounts.proposal_instruction.executed = false;
+        ctx.accounts.proposal_instruction.instructions = instructions;
+        ctx.accounts.proposal_instruction.bump = *ctx.bumps.get("proposal_instruction").unwrap();
+        Ok(())
+    }
+
+    // Execute Proposal (Permissionless -- once PASSED and the timelock is over)
+    // Every account the instructions touch, including the target programs, goes in remaining_accounts
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        let finalized_at = match ctx.accounts.proposal.finalized_at {
+            Some(finalized_at) => finalized_at,
+            None => return err!(PocketErrors::ProposalNotFinalized),
+        };
+        let clock = Clock::get().unwrap();
+        let unlocks_at = finalized_at
+            .checked_add(ctx.accounts.proposal_instruction.hold_up_seconds)
+            .ok_or(PocketErrors::Overflow)?;
+        if clock.unix_timestamp < unlocks_at {
+            return err!(PocketErrors::TimelockNotElapsed);
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEEDS_FACTION,
+            ctx.accounts.faction.id.as_bytes(),
+            &[ctx.accounts.faction.bump],
+        ]];
+
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.faction.to_account_info());
+
+        for ix in ctx.accounts.proposal_instruction.instructions.iter() {
+            if !ix.is_whitelisted() {
+                return err!(PocketErrors::ProgramNotWhitelisted);
+            }
+            invoke_signed(&Instruction::from(ix), &account_infos, signer_seeds)?;
+        }
+
+        ctx.accounts.proposal_instruction.executed = true;
+        let old_status = ctx.accounts.proposal.transition(ProposalStatus::EXECUTED)?;
+
+        emit!(ProposalStatusChanged {
+            proposal: ctx.accounts.proposal.key(),
+            old_status,
+            new_status: ctx.accounts.proposal.status.clone(),
+            winning_option: ctx.accounts.proposal.winning_option,
+            tallies: ctx.accounts.proposal.tallies.clone(),
+        });
         Ok(())
     }
     pub fn delete_proposal(_ctx: Context<DeleteProposalAccount>) -> Result<()> {
         Ok(())
     }
     // Update Proposal Status (Server Only)
+    // Server can open a DRAFT for voting or cancel it, results come from finalize_proposal
     pub fn update_proposal(ctx: Context<UpdateProposal>, new_status: ProposalStatus) -> Result<()> {
-        ctx.accounts.proposal.status = new_status;
+        if new_status != ProposalStatus::VOTING && new_status != ProposalStatus::CANCELLED {
+            return err!(PocketErrors::IllegalTransition);
+        }
+        let old_status = ctx.accounts.proposal.transition(new_status)?;
+        ctx.accounts
+            .faction
+            .track_proposal(&old_status, &ctx.accounts.proposal.status)?;
+
+        emit!(ProposalStatusChanged {
+            proposal: ctx.accounts.proposal.key(),
+            old_status,
+            new_status: ctx.accounts.proposal.status.clone(),
+            winning_option: ctx.accounts.proposal.winning_option,
+            tallies: ctx.accounts.proposal.tallies.clone(),
+        });
         Ok(())
     }
-    // Vote on Proposal
-    pub fn vote_on_proposal(ctx: Context<Vote>, vote_amt: u64) -> Result<()> {
-        // Check that the Vote Amt is something that the Citizen has
-        if ctx.accounts.citizen.total_voting_power - ctx.accounts.citizen.max_pledged_voting_power
-            < vote_amt
+
+    // Cancel Proposal (Proposer Only -- before the result is in)
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let clock = Clock::get().unwrap();
+        if ctx.accounts.proposal.status == ProposalStatus::VOTING
+            && clock.unix_timestamp > ctx.accounts.proposal.end_ts
         {
-            return err!(PocketErrors::CitizenLacksVotingPower);
+            return err!(PocketErrors::VotingClosed);
+        }
+
+        let old_status = ctx
+            .accounts
+            .proposal
+            .transition(ProposalStatus::CANCELLED)?;
+        ctx.accounts
+            .faction
+            .track_proposal(&old_status, &ctx.accounts.proposal.status)?;
+
+        emit!(ProposalStatusChanged {
+            proposal: ctx.accounts.proposal.key(),
+            old_status,
+            new_status: ctx.accounts.proposal.status.clone(),
+            winning_option: ctx.accounts.proposal.winning_option,
+            tallies: ctx.accounts.proposal.tallies.clone(),
+        });
+        Ok(())
+    }
+
+    // Veto Proposal (Faction Council Only -- any time before it's executed)
+    pub fn veto_proposal(ctx: Context<VetoProposal>) -> Result<()> {
+        let old_status = ctx.accounts.proposal.transition(ProposalStatus::VETOED)?;
+        ctx.accounts
+            .faction
+            .track_proposal(&old_status, &ctx.accounts.proposal.status)?;
+
+        emit!(ProposalStatusChanged {
+            proposal: ctx.accounts.proposal.key(),
+            old_status,
+            new_status: ctx.accounts.proposal.status.clone(),
+            winning_option: ctx.accounts.proposal.winning_option,
+            tallies: ctx.accounts.proposal.tallies.clone(),
+        });
+        Ok(())
+    }
+
+    // Finalize Proposal (Permissionless -- anyone can crank once voting has ended)
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let clock = Clock::get().unwrap();
+        if clock.unix_timestamp <= ctx.accounts.proposal.end_ts {
+            return err!(PocketErrors::VotingStillOpen);
+        }
+
+        let old_status = ctx
+            .accounts
+            .proposal
+            .finalize(&ctx.accounts.faction, clock.unix_timestamp)?;
+        ctx.accounts
+            .faction
+            .track_proposal(&old_status, &ctx.accounts.proposal.status)?;
+
+        emit!(ProposalStatusChanged {
+            proposal: ctx.accounts.proposal.key(),
+            old_status,
+            new_status: ctx.accounts.proposal.status.clone(),
+            winning_option: ctx.accounts.proposal.winning_option,
+            tallies: ctx.accounts.proposal.tallies.clone(),
+        });
+        Ok(())
+    }
+    // Vote on Proposal
+    pub fn vote_on_proposal(ctx: Context<Vote>, vote_amt: u64, option: u8) -> Result<()> {
+        if option as usize >= ctx.accounts.proposal.options.len() {
+            return err!(PocketErrors::InvalidProposalOption);
+        }
+
+        // Conviction proposals can only be supported
+        if ctx.accounts.proposal.proposal_type == ProposalType::Conviction && option != OPTION_FOR {
+            return err!(PocketErrors::InvalidProposalOption);
+        }
+
+        let clock = Clock::get().unwrap();
+        if !ctx.accounts.proposal.is_voting_open(clock.unix_timestamp) {
+            return err!(PocketErrors::VotingClosed);
+        }
+        ctx.accounts
+            .proposal
+            .accrue_conviction(clock.unix_timestamp)?;
+
+        // Can't vote with power picked up after the proposal was created
+        let snapshot_power = ctx
+            .accounts
+            .citizen_checkpoints
+            .power_at(ctx.accounts.proposal.snapshot_slot)?;
+
+        // Check that the Vote Amt is something that the Citizen has, power delegated for the
+        // proposal's category is used up first. Topic power isn't checkpointed, the snapshot
+        // only caps the general part
+        let category = ctx.accounts.proposal.category;
+        let topic_amt = ctx
+            .accounts
+            .citizen
+            .pledge_voting_power(category, vote_amt)?;
+        if vote_amt - topic_amt > snapshot_power {
+            return err!(PocketErrors::ExceedsSnapshotVotingPower);
         } else {
-            ctx.accounts.citizen.max_pledged_voting_power += vote_amt;
             ctx.accounts.vote.citizen = ctx.accounts.citizen.key();
             ctx.accounts.vote.vote_amt = vote_amt;
-            ctx.accounts.proposal.vote_amt += vote_amt;
+            ctx.accounts.vote.topic_amt = topic_amt;
+            ctx.accounts.vote.weight = ctx.accounts.proposal.vote_weight(vote_amt);
+            ctx.accounts.vote.option = option;
+            ctx.accounts.vote.bump = *ctx.bumps.get("vote").unwrap();
+            ctx.accounts.proposal.add_vote(&ctx.accounts.vote)?;
+            ctx.accounts.citizen.open_vote(category)?;
 
-            // Check if vote passed, is so, set it to passed
-            if ctx.accounts.proposal.vote_amt >= ctx.accounts.faction.threshold_to_pass {
-                ctx.accounts.proposal.status = ProposalStatus::PASSED;
-            }
+            emit!(VoteCast {
+                vote: ctx.accounts.vote.key(),
+                citizen: ctx.accounts.citizen.key(),
+                proposal: ctx.accounts.proposal.key(),
+                option,
+                vote_amt,
+                weight: ctx.accounts.vote.weight,
+                option_tally: ctx.accounts.proposal.tallies[option as usize],
+                citizen_pledged_voting_power: ctx.accounts.citizen.max_pledged_voting_power,
+            });
         }
         Ok(())
     }
 
     // Increment/Decerement Vote Amount
     pub fn update_vote(ctx: Context<UpdateVote>, vote_amt: u64, is_increment: bool) -> Result<()> {
+        let clock = Clock::get().unwrap();
+        if !ctx.accounts.proposal.is_voting_open(clock.unix_timestamp) {
+            return err!(PocketErrors::VotingClosed);
+        }
+        ctx.accounts
+            .proposal
+            .accrue_conviction(clock.unix_timestamp)?;
+
+        let old_vote_amt = ctx.accounts.vote.vote_amt;
+        let category = ctx.accounts.proposal.category;
+        if !is_increment && vote_amt > ctx.accounts.vote.vote_amt {
+            return err!(PocketErrors::InvalidVotingPowerDecrement);
+        }
+
+        // Take the old weight out and put the recomputed one back in, sqrt isn't additive
+        ctx.accounts.proposal.remove_vote(&ctx.accounts.vote)?;
         if is_increment {
-            if ctx.accounts.citizen.total_voting_power
-                - ctx.accounts.citizen.max_pledged_voting_power
-                < vote_amt
-            {
-                return err!(PocketErrors::CitizenLacksVotingPower);
-            } else {
-                ctx.accounts.citizen.max_pledged_voting_power += vote_amt;
-                ctx.accounts.proposal.vote_amt += vote_amt;
-                ctx.accounts.vote.vote_amt += vote_amt;
-
-                // Check if vote passed, is so, set it to passed
-                if ctx.accounts.proposal.vote_amt >= ctx.accounts.faction.threshold_to_pass {
-                    ctx.accounts.proposal.status = ProposalStatus::PASSED;
-                }
+            let topic_amt = ctx
+                .accounts
+                .citizen
+                .pledge_voting_power(category, vote_amt)?;
+            ctx.accounts.vote.vote_amt = safe_add(ctx.accounts.vote.vote_amt, vote_amt)?;
+            ctx.accounts.vote.topic_amt = safe_add(ctx.accounts.vote.topic_amt, topic_amt)?;
+
+            let snapshot_power = ctx
+                .accounts
+                .citizen_checkpoints
+                .power_at(ctx.accounts.proposal.snapshot_slot)?;
+            if ctx.accounts.vote.vote_amt - ctx.accounts.vote.topic_amt > snapshot_power {
+                return err!(PocketErrors::ExceedsSnapshotVotingPower);
             }
         } else {
-            // Decrement
-            if vote_amt > ctx.accounts.vote.vote_amt {
-                return err!(PocketErrors::InvalidVotingPowerDecrement);
-            }
-
-            ctx.accounts.citizen.max_pledged_voting_power -= vote_amt;
-            ctx.accounts.proposal.vote_amt -= vote_amt;
-            ctx.accounts.vote.vote_amt -= vote_amt;
+            let (general_amt, topic_amt) = ctx.accounts.vote.split_release(vote_amt);
+            ctx.accounts
+                .citizen
+                .release_voting_power(category, general_amt, topic_amt)?;
+            ctx.accounts.vote.vote_amt = safe_sub(ctx.accounts.vote.vote_amt, vote_amt)?;
+            ctx.accounts.vote.topic_amt = safe_sub(ctx.accounts.vote.topic_amt, topic_amt)?;
         }
+        ctx.accounts.vote.weight = ctx
+            .accounts
+            .proposal
+            .vote_weight(ctx.accounts.vote.vote_amt);
+        ctx.accounts.proposal.add_vote(&ctx.accounts.vote)?;
+
+        emit!(VoteUpdated {
+            vote: ctx.accounts.vote.key(),
+            citizen: ctx.accounts.citizen.key(),
+            proposal: ctx.accounts.proposal.key(),
+            option: ctx.accounts.vote.option,
+            old_vote_amt,
+            new_vote_amt: ctx.accounts.vote.vote_amt,
+            new_weight: ctx.accounts.vote.weight,
+            option_tally: ctx.accounts.proposal.tallies[ctx.accounts.vote.option as usize],
+            citizen_pledged_voting_power: ctx.accounts.citizen.max_pledged_voting_power,
+        });
         Ok(())
     }
 
     // Close Vote for Finished Proposals
+    // Closing during the voting window withdraws the vote, after finalization the tallies are left alone
     pub fn close_vote_account(ctx: Context<CloseVoteAccount>) -> Result<()> {
-        ctx.accounts.citizen.max_pledged_voting_power -= ctx.accounts.vote.vote_amt;
-        ctx.accounts.proposal.vote_amt -= ctx.accounts.vote.vote_amt;
+        let removed_from_tally = ctx.accounts.proposal.status == ProposalStatus::VOTING;
+        if removed_from_tally {
+            let clock = Clock::get().unwrap();
+            if !ctx.accounts.proposal.is_voting_open(clock.unix_timestamp) {
+                return err!(PocketErrors::ProposalNotFinalized);
+            }
+            ctx.accounts
+                .proposal
+                .accrue_conviction(clock.unix_timestamp)?;
+            ctx.accounts.proposal.remove_vote(&ctx.accounts.vote)?;
+        }
+
+        let (general_amt, topic_amt) = ctx.accounts.vote.split_release(ctx.accounts.vote.vote_amt);
+        ctx.accounts.citizen.release_voting_power(
+            ctx.accounts.proposal.category,
+            general_amt,
+            topic_amt,
+        )?;
+        ctx.accounts
+            .citizen
+            .close_vote(ctx.accounts.proposal.category)?;
+
+        emit!(VoteClosed {
+            vote: ctx.accounts.vote.key(),
+            citizen: ctx.accounts.citizen.key(),
+            proposal: ctx.accounts.proposal.key(),
+            option: ctx.accounts.vote.option,
+            vote_amt: ctx.accounts.vote.vote_amt,
+            removed_from_tally,
+            option_tally: ctx.accounts.proposal.tallies[ctx.accounts.vote.option as usize],
+            citizen_pledged_voting_power: ctx.accounts.citizen.max_pledged_voting_power,
+        });
         Ok(())
     }
 
@@ -196,42 +1294,245 @@ pub mod pockets_program {
     // Vote
     // Transfer Vote
     pub fn transfer_votes(ctx: Context<TransferVotes>, vote_amt: u64) -> Result<()> {
-        if ctx.accounts.citizen.total_voting_power - ctx.accounts.citizen.max_pledged_voting_power
-            < vote_amt
-            || ctx.accounts.citizen.granted_voting_power < vote_amt
-        {
+        if ctx.accounts.citizen.transferable_voting_power()? < vote_amt {
             return err!(PocketErrors::InvalidVotingPowerDecrement);
         }
 
-        ctx.accounts.citizen.total_voting_power -= vote_amt;
-        ctx.accounts.citizen.granted_voting_power -= vote_amt;
+        ctx.accounts.citizen.total_voting_power =
+            safe_sub(ctx.accounts.citizen.total_voting_power, vote_amt)?;
+        ctx.accounts.citizen.granted_voting_power =
+            safe_sub(ctx.accounts.citizen.granted_voting_power, vote_amt)?;
+
+        ctx.accounts.vote_recepient.granted_voting_power =
+            safe_add(ctx.accounts.vote_recepient.granted_voting_power, vote_amt)?;
+        ctx.accounts.vote_recepient.total_voting_power =
+            safe_add(ctx.accounts.vote_recepient.total_voting_power, vote_amt)?;
+
+        let citizen_power = ctx.accounts.citizen.total_voting_power;
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts.citizen_checkpoints.record(citizen_power)?;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
 
-        ctx.accounts.vote_recepient.granted_voting_power += vote_amt;
-        ctx.accounts.vote_recepient.total_voting_power += vote_amt;
+        emit!(VotesTransferred {
+            from: ctx.accounts.citizen.key(),
+            to: ctx.accounts.vote_recepient.key(),
+            vote_amt,
+            from_total_voting_power: ctx.accounts.citizen.total_voting_power,
+            to_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
         Ok(())
     }
 
     // Delegate Vote
-    // Can only delegate granted power
-    pub fn delegate_votes(ctx: Context<DelegateVote>, vote_amt: u64) -> Result<()> {
-        if ctx.accounts.citizen.total_voting_power - ctx.accounts.citizen.max_pledged_voting_power
-            < vote_amt
-            || ctx.accounts.citizen.granted_voting_power < vote_amt
-        {
+    // Can only delegate granted power, optionally until expires_at. Delegating in a topic
+    // category only lets the delegate vote with it on proposals in that category
+    pub fn delegate_votes(
+        ctx: Context<DelegateVote>,
+        vote_amt: u64,
+        expires_at: Option<i64>,
+        category: ProposalCategory,
+    ) -> Result<()> {
+        if vote_amt == 0 {
+            return err!(PocketErrors::ZeroDelegationAmount);
+        }
+        if ctx.accounts.citizen.unlocked_voting_power()? < vote_amt {
             return err!(PocketErrors::InvalidVotingPowerDecrement);
         }
+        if let Some(expires_at) = expires_at {
+            if expires_at <= Clock::get()?.unix_timestamp {
+                return err!(PocketErrors::InvalidDelegationExpiry);
+            }
+        }
 
-        ctx.accounts.citizen.max_pledged_voting_power += vote_amt;
-        ctx.accounts.vote_recepient.delegated_voting_power += vote_amt;
-        ctx.accounts.vote_recepient.total_voting_power += vote_amt;
+        ctx.accounts.citizen.max_pledged_voting_power =
+            safe_add(ctx.accounts.citizen.max_pledged_voting_power, vote_amt)?;
+        ctx.accounts
+            .vote_recepient
+            .add_delegated_voting_power(category, vote_amt)?;
+
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
 
         ctx.accounts.delegation_record.citizen = ctx.accounts.citizen.key();
         ctx.accounts.delegation_record.delegate = ctx.accounts.vote_recepient.key();
         ctx.accounts.delegation_record.vote_amt = vote_amt;
+        ctx.accounts.delegation_record.forwarded_vote_amt = 0;
+        ctx.accounts.delegation_record.expires_at = expires_at;
+        ctx.accounts.delegation_record.category = category;
+        ctx.accounts.delegation_record.bump = *ctx.bumps.get("delegation_record").unwrap();
+
+        emit!(DelegationCreated {
+            delegation: ctx.accounts.delegation_record.key(),
+            citizen: ctx.accounts.citizen.key(),
+            delegate: ctx.accounts.vote_recepient.key(),
+            vote_amt,
+            expires_at,
+            category,
+            citizen_pledged_voting_power: ctx.accounts.citizen.max_pledged_voting_power,
+            delegate_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
+        Ok(())
+    }
+
+    // Revoke Delegation (Delegator Only)
+    // Takes the whole delegation back, pulling it out of the delegate's votes if it's pledged.
+    // Pass every one of the delegate's (ProposalVote, Proposal) pairs in remaining_accounts,
+    // followed by every hop it was forwarded on to (see reclaim_forwarded)
+    pub fn revoke_delegation<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeDelegation<'info>>,
+    ) -> Result<()> {
+        let (vote_accounts, hop_accounts) = split_unwind_accounts(ctx.remaining_accounts);
+        let delegation_key = ctx.accounts.delegation_record.key();
+        let forwarded_vote_amt = ctx.accounts.delegation_record.forwarded_vote_amt;
+        reclaim_forwarded(
+            delegation_key,
+            &mut ctx.accounts.delegation_record,
+            &mut ctx.accounts.vote_recepient,
+            forwarded_vote_amt,
+            hop_accounts,
+        )?;
+
+        let delegate_key = ctx.accounts.vote_recepient.key();
+        let unwound_vote_amt = unwind_delegate_votes(
+            &mut ctx.accounts.vote_recepient,
+            delegate_key,
+            ctx.accounts.delegation_record.category,
+            ctx.accounts.delegation_record.vote_amt,
+            vote_accounts,
+        )?;
+        let vote_amt = release_delegation(
+            &mut ctx.accounts.citizen,
+            &mut ctx.accounts.vote_recepient,
+            &mut ctx.accounts.delegation_record,
+        )?;
+
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
+
+        emit!(DelegationRevoked {
+            delegation: ctx.accounts.delegation_record.key(),
+            citizen: ctx.accounts.citizen.key(),
+            delegate: delegate_key,
+            vote_amt,
+            unwound_vote_amt,
+            expired: false,
+            delegate_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
         Ok(())
     }
 
-    pub fn delete_vote_delegation(_ctx: Context<DeleteVoteDelegation>) -> Result<()> {
+    // Expire Delegation (Permissionless crank -- once expires_at has passed)
+    // Same unwinding as revoke_delegation, the emptied record is left for the delegator to close
+    pub fn expire_delegation<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExpireDelegation<'info>>,
+    ) -> Result<()> {
+        match ctx.accounts.delegation_record.expires_at {
+            Some(expires_at) if Clock::get()?.unix_timestamp >= expires_at => {}
+            _ => return err!(PocketErrors::DelegationNotExpired),
+        }
+
+        let (vote_accounts, hop_accounts) = split_unwind_accounts(ctx.remaining_accounts);
+        let delegation_key = ctx.accounts.delegation_record.key();
+        let forwarded_vote_amt = ctx.accounts.delegation_record.forwarded_vote_amt;
+        reclaim_forwarded(
+            delegation_key,
+            &mut ctx.accounts.delegation_record,
+            &mut ctx.accounts.vote_recepient,
+            forwarded_vote_amt,
+            hop_accounts,
+        )?;
+
+        let delegate_key = ctx.accounts.vote_recepient.key();
+        let unwound_vote_amt = unwind_delegate_votes(
+            &mut ctx.accounts.vote_recepient,
+            delegate_key,
+            ctx.accounts.delegation_record.category,
+            ctx.accounts.delegation_record.vote_amt,
+            vote_accounts,
+        )?;
+        let vote_amt = release_delegation(
+            &mut ctx.accounts.citizen,
+            &mut ctx.accounts.vote_recepient,
+            &mut ctx.accounts.delegation_record,
+        )?;
+
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
+
+        emit!(DelegationRevoked {
+            delegation: ctx.accounts.delegation_record.key(),
+            citizen: ctx.accounts.citizen.key(),
+            delegate: delegate_key,
+            vote_amt,
+            unwound_vote_amt,
+            expired: true,
+            delegate_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
+        Ok(())
+    }
+
+    // Delete Vote Delegation (Server or Delegator)
+    // Hands back whatever is still delegated before closing, unwinding the delegate's votes and
+    // forwarded hops like revoke_delegation, with the same remaining_accounts
+    pub fn delete_vote_delegation<'info>(
+        ctx: Context<'_, '_, '_, 'info, DeleteVoteDelegation<'info>>,
+    ) -> Result<()> {
+        let closer = ctx.accounts.closer.key();
+        let is_delegator = match &ctx.accounts.closer_ata {
+            Some(ata) => ata.amount == 1,
+            None => false,
+        };
+        if closer != ctx.accounts.config.authority && !is_delegator {
+            return err!(PocketErrors::NotDelegator);
+        }
+
+        let (vote_accounts, hop_accounts) = split_unwind_accounts(ctx.remaining_accounts);
+        let delegation_key = ctx.accounts.delegation.key();
+        let forwarded_vote_amt = ctx.accounts.delegation.forwarded_vote_amt;
+        reclaim_forwarded(
+            delegation_key,
+            &mut ctx.accounts.delegation,
+            &mut ctx.accounts.vote_recepient,
+            forwarded_vote_amt,
+            hop_accounts,
+        )?;
+
+        let delegate_key = ctx.accounts.vote_recepient.key();
+        let unwound_vote_amt = unwind_delegate_votes(
+            &mut ctx.accounts.vote_recepient,
+            delegate_key,
+            ctx.accounts.delegation.category,
+            ctx.accounts.delegation.vote_amt,
+            vote_accounts,
+        )?;
+        let vote_amt = release_delegation(
+            &mut ctx.accounts.citizen,
+            &mut ctx.accounts.vote_recepient,
+            &mut ctx.accounts.delegation,
+        )?;
+
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
+
+        emit!(DelegationRevoked {
+            delegation: ctx.accounts.delegation.key(),
+            citizen: ctx.accounts.citizen.key(),
+            delegate: delegate_key,
+            vote_amt,
+            unwound_vote_amt,
+            expired: false,
+            delegate_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
         Ok(())
     }
 
@@ -241,51 +1542,205 @@ pub mod pockets_program {
         vote_amt: u64,
         is_increment: bool,
     ) -> Result<()> {
+        if vote_amt == 0 {
+            return err!(PocketErrors::ZeroDelegationAmount);
+        }
+        let old_vote_amt = ctx.accounts.delegation_record.vote_amt;
+        let category = ctx.accounts.delegation_record.category;
         if is_increment {
-            if ctx.accounts.citizen.total_voting_power
-                - ctx.accounts.citizen.max_pledged_voting_power
-                < vote_amt
-                || ctx.accounts.citizen.granted_voting_power < vote_amt
-            {
+            if ctx.accounts.citizen.unlocked_voting_power()? < vote_amt {
                 return err!(PocketErrors::InvalidVotingPowerDecrement);
             }
-            ctx.accounts.citizen.max_pledged_voting_power += vote_amt;
-            ctx.accounts.vote_recepient.delegated_voting_power += vote_amt;
-            ctx.accounts.vote_recepient.total_voting_power += vote_amt;
-            ctx.accounts.delegation_record.vote_amt += vote_amt;
+            ctx.accounts.citizen.max_pledged_voting_power =
+                safe_add(ctx.accounts.citizen.max_pledged_voting_power, vote_amt)?;
+            ctx.accounts
+                .vote_recepient
+                .add_delegated_voting_power(category, vote_amt)?;
+            ctx.accounts.delegation_record.vote_amt =
+                safe_add(ctx.accounts.delegation_record.vote_amt, vote_amt)?;
         } else {
             // Decrement
-            if ctx.accounts.vote_recepient.total_voting_power
-                - ctx.accounts.vote_recepient.max_pledged_voting_power
+            if safe_sub(
+                ctx.accounts.delegation_record.vote_amt,
+                ctx.accounts.delegation_record.forwarded_vote_amt,
+            )? < vote_amt
+            {
+                return err!(PocketErrors::DelegationForwarded);
+            }
+            if ctx
+                .accounts
+                .vote_recepient
+                .free_delegated_voting_power(category)?
                 < vote_amt
-                || ctx.accounts.vote_recepient.delegated_voting_power < vote_amt
             {
                 return err!(PocketErrors::DelegatePendingVotes);
             }
 
-            ctx.accounts.citizen.max_pledged_voting_power -= vote_amt;
-            ctx.accounts.vote_recepient.delegated_voting_power -= vote_amt;
-            ctx.accounts.vote_recepient.total_voting_power -= vote_amt;
-            ctx.accounts.delegation_record.vote_amt -= vote_amt;
+            ctx.accounts.citizen.max_pledged_voting_power =
+                safe_sub(ctx.accounts.citizen.max_pledged_voting_power, vote_amt)?;
+            ctx.accounts
+                .vote_recepient
+                .remove_delegated_voting_power(category, vote_amt)?;
+            ctx.accounts.delegation_record.vote_amt =
+                safe_sub(ctx.accounts.delegation_record.vote_amt, vote_amt)?;
         }
+
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
+
+        emit!(DelegationAdjusted {
+            delegation: ctx.accounts.delegation_record.key(),
+            citizen: ctx.accounts.citizen.key(),
+            delegate: ctx.accounts.vote_recepient.key(),
+            old_vote_amt,
+            new_vote_amt: ctx.accounts.delegation_record.vote_amt,
+            citizen_pledged_voting_power: ctx.accounts.citizen.max_pledged_voting_power,
+            delegate_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
         Ok(())
     }
 
     // Return Vote Delegation
-    // Decrement from the Delgation's side
-    pub fn return_vote_delegation(ctx: Context<AdjustDelegation>, vote_amt: u64) -> Result<()> {
-        if ctx.accounts.citizen.total_voting_power - ctx.accounts.citizen.delegated_voting_power
+    // Decrement from the Delgation's side, signed by the delegate (vote_recepient)
+    // Anything the delegate forwarded on is pulled back first, pass the forwarded hops
+    // in remaining_accounts (see reclaim_forwarded)
+    pub fn return_vote_delegation<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReturnDelegation<'info>>,
+        vote_amt: u64,
+    ) -> Result<()> {
+        if ctx.accounts.delegation_record.vote_amt < vote_amt {
+            return err!(PocketErrors::InvalidVotingPowerDecrement);
+        }
+        let unforwarded = safe_sub(
+            ctx.accounts.delegation_record.vote_amt,
+            ctx.accounts.delegation_record.forwarded_vote_amt,
+        )?;
+        let from_forwarded = vote_amt.saturating_sub(unforwarded);
+        let delegation_key = ctx.accounts.delegation_record.key();
+        reclaim_forwarded(
+            delegation_key,
+            &mut ctx.accounts.delegation_record,
+            &mut ctx.accounts.vote_recepient,
+            from_forwarded,
+            ctx.remaining_accounts,
+        )?;
+
+        let category = ctx.accounts.delegation_record.category;
+        if ctx
+            .accounts
+            .vote_recepient
+            .free_delegated_voting_power(category)?
             < vote_amt
-            || ctx.accounts.citizen.delegated_voting_power < vote_amt
         {
             return err!(PocketErrors::InvalidVotingPowerDecrement);
         }
 
-        ctx.accounts.citizen.total_voting_power -= vote_amt;
-        ctx.accounts.citizen.delegated_voting_power -= vote_amt;
-        ctx.accounts.vote_recepient.max_pledged_voting_power -= vote_amt;
+        ctx.accounts
+            .vote_recepient
+            .remove_delegated_voting_power(category, vote_amt)?;
+        ctx.accounts.citizen.max_pledged_voting_power =
+            safe_sub(ctx.accounts.citizen.max_pledged_voting_power, vote_amt)?;
+
+        ctx.accounts.delegation_record.vote_amt =
+            safe_sub(ctx.accounts.delegation_record.vote_amt, vote_amt)?;
 
-        ctx.accounts.delegation_record.vote_amt -= vote_amt;
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
+
+        emit!(DelegationReturned {
+            delegation: ctx.accounts.delegation_record.key(),
+            citizen: ctx.accounts.delegation_record.citizen,
+            delegate: ctx.accounts.delegation_record.delegate,
+            returned_vote_amt: vote_amt,
+            remaining_vote_amt: ctx.accounts.delegation_record.vote_amt,
+        });
+        Ok(())
+    }
+
+    // Forward Delegation (Delegate Only -- liquid mode)
+    // Passes on power this citizen was delegated, parent is the VoteDelegation or DelegationPath
+    // it came in through. The power is pledged here until it's returned back up the chain
+    pub fn forward_delegation(ctx: Context<ForwardDelegation>, vote_amt: u64) -> Result<()> {
+        if vote_amt == 0 {
+            return err!(PocketErrors::ZeroDelegationAmount);
+        }
+        let forwarder = ctx.accounts.citizen.key();
+        let delegate = ctx.accounts.vote_recepient.key();
+
+        let parent_info = ctx.accounts.parent.to_account_info();
+        let mut path = match Account::<VoteDelegation>::try_from(&parent_info) {
+            Ok(mut parent) => {
+                // Topic power stays with the delegate it was given to
+                if parent.delegate != forwarder || parent.category != ProposalCategory::General {
+                    return err!(PocketErrors::InvalidDelegationParent);
+                }
+                if safe_sub(parent.vote_amt, parent.forwarded_vote_amt)? < vote_amt {
+                    return err!(PocketErrors::InvalidVotingPowerDecrement);
+                }
+                parent.forwarded_vote_amt = safe_add(parent.forwarded_vote_amt, vote_amt)?;
+                parent.exit(&crate::ID)?;
+                vec![parent.citizen, parent.delegate]
+            }
+            Err(_) => {
+                let mut parent = Account::<DelegationPath>::try_from(&parent_info)?;
+                if parent.delegate != forwarder {
+                    return err!(PocketErrors::InvalidDelegationParent);
+                }
+                if safe_sub(parent.vote_amt, parent.forwarded_vote_amt)? < vote_amt {
+                    return err!(PocketErrors::InvalidVotingPowerDecrement);
+                }
+                parent.forwarded_vote_amt = safe_add(parent.forwarded_vote_amt, vote_amt)?;
+                parent.exit(&crate::ID)?;
+                parent.path.clone()
+            }
+        };
+
+        if path.contains(&delegate) {
+            return err!(PocketErrors::DelegationCycle);
+        }
+        path.push(delegate);
+        // The first hop is the original delegation, everything after it was forwarded
+        if path.len() - 2 > ctx.accounts.faction.liquid_delegation_depth as usize {
+            return err!(PocketErrors::DelegationTooDeep);
+        }
+        if ctx.accounts.citizen.free_voting_power()? < vote_amt {
+            return err!(PocketErrors::DelegatePendingVotes);
+        }
+
+        ctx.accounts.citizen.max_pledged_voting_power =
+            safe_add(ctx.accounts.citizen.max_pledged_voting_power, vote_amt)?;
+        ctx.accounts.vote_recepient.delegated_voting_power =
+            safe_add(ctx.accounts.vote_recepient.delegated_voting_power, vote_amt)?;
+        ctx.accounts.vote_recepient.total_voting_power =
+            safe_add(ctx.accounts.vote_recepient.total_voting_power, vote_amt)?;
+
+        let recepient_power = ctx.accounts.vote_recepient.total_voting_power;
+        ctx.accounts
+            .vote_recepient_checkpoints
+            .record(recepient_power)?;
+
+        ctx.accounts.delegation_path.parent = ctx.accounts.parent.key();
+        ctx.accounts.delegation_path.citizen = forwarder;
+        ctx.accounts.delegation_path.delegate = delegate;
+        ctx.accounts.delegation_path.vote_amt = vote_amt;
+        ctx.accounts.delegation_path.forwarded_vote_amt = 0;
+        ctx.accounts.delegation_path.path = path;
+        ctx.accounts.delegation_path.payer = ctx.accounts.wallet.key();
+        ctx.accounts.delegation_path.bump = *ctx.bumps.get("delegation_path").unwrap();
+
+        emit!(DelegationForwarded {
+            delegation_path: ctx.accounts.delegation_path.key(),
+            parent: ctx.accounts.parent.key(),
+            citizen: forwarder,
+            delegate,
+            vote_amt,
+            path: ctx.accounts.delegation_path.path.clone(),
+            delegate_total_voting_power: ctx.accounts.vote_recepient.total_voting_power,
+        });
         Ok(())
     }
 
@@ -297,6 +1752,13 @@ pub mod pockets_program {
         ctx.accounts.rf.refresh_seconds = None;
         ctx.accounts.rf.is_harvestable = false;
         ctx.accounts.rf.initial_claimant = None;
+        ctx.accounts.rf.last_harvested = None;
+        ctx.accounts.rf.bump = *ctx.bumps.get("rf").unwrap();
+
+        emit!(ResourceFieldAllocated {
+            rf: ctx.accounts.rf.key(),
+            id: ctx.accounts.rf.id.clone(),
+        });
         Ok(())
     }
 
@@ -352,8 +1814,528 @@ pub mod pockets_program {
             // NO -> Increment Times Developed
             ctx.accounts.rf.times_developed += 1;
         }
+
+        emit!(ResourceFieldDeveloped {
+            rf: ctx.accounts.rf.key(),
+            citizen: ctx.accounts.citizen.key(),
+            is_harvestable: ctx.accounts.rf.is_harvestable,
+            times_developed: ctx.accounts.rf.times_developed,
+            harvest: ctx.accounts.rf.harvest.clone(),
+            refresh_seconds: ctx.accounts.rf.refresh_seconds,
+        });
+        Ok(())
+    }
+
+    // Register Resource Mint -- Server Only
+    // One mint per entry in RESOURCES, minted by the program when fields are harvested
+    pub fn register_resource_mint(
+        _ctx: Context<RegisterResourceMint>,
+        resource: String,
+    ) -> Result<()> {
+        if !RESOURCES.contains(&resource.as_str()) {
+            return err!(PocketErrors::InvalidResource);
+        }
+        Ok(())
+    }
+
+    // Harvest Resource Field -- Initial Claimant Only
+    // Mints the harvest to the wallet's token account for that resource
+    pub fn harvest_resource_field(ctx: Context<HarvestRF>) -> Result<()> {
+        let harvest = match ctx.accounts.rf.harvest.clone() {
+            Some(harvest) => harvest,
+            None => return err!(PocketErrors::ResourceFieldNotDeveloped),
+        };
+
+        // Timers are stored in ms (see RF_MIN_TIMER / RF_MAX_TIMER)
+        let clock = Clock::get().unwrap();
+        if let (Some(last_harvested), Some(refresh)) = (
+            ctx.accounts.rf.last_harvested,
+            ctx.accounts.rf.refresh_seconds,
+        ) {
+            let elapsed_ms = (clock.unix_timestamp - last_harvested) as u64 * 1000;
+            if elapsed_ms < refresh {
+                return err!(PocketErrors::ResourceFieldNotReady);
+            }
+        }
+
+        let mint_authority_bump = *ctx.bumps.get("mint_authority").unwrap();
+        let signer_seeds: &[&[&[u8]]] = &[&[SEEDS_MINT_AUTHORITY, &[mint_authority_bump]]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.resource_mint.to_account_info(),
+                    to: ctx.accounts.resource_token_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            harvest.harvest,
+        )?;
+        ctx.accounts.rf.last_harvested = Some(clock.unix_timestamp);
+
+        emit!(ResourceFieldHarvested {
+            rf: ctx.accounts.rf.key(),
+            citizen: ctx.accounts.citizen.key(),
+            resource: harvest.resource,
+            resource_mint: ctx.accounts.resource_mint.key(),
+            recipient: ctx.accounts.resource_token_account.key(),
+            amount: harvest.harvest,
+            harvested_at: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Pocket AMM
+    // Create Pool -- Server Only
+    // Passing a faction sends treasury_fee_bps of every swap to that faction's treasury
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        _resource_a: String,
+        _resource_b: String,
+        fee_bps: u16,
+        treasury_fee_bps: u16,
+    ) -> Result<()> {
+        if u32::from(fee_bps) + u32::from(treasury_fee_bps) > u32::from(MAX_POOL_FEE_BPS) {
+            return err!(PocketErrors::InvalidPoolFee);
+        }
+
+        let treasury = ctx.accounts.faction.as_ref().map(|faction| {
+            Pubkey::find_program_address(&[SEEDS_FACTION, faction.key().as_ref()], &crate::ID).0
+        });
+
+        ctx.accounts.pool.mint_a = ctx.accounts.mint_a.key();
+        ctx.accounts.pool.mint_b = ctx.accounts.mint_b.key();
+        ctx.accounts.pool.vault_a = ctx.accounts.vault_a.key();
+        ctx.accounts.pool.vault_b = ctx.accounts.vault_b.key();
+        ctx.accounts.pool.lp_mint = ctx.accounts.lp_mint.key();
+        ctx.accounts.pool.fee_bps = fee_bps;
+        ctx.accounts.pool.treasury_fee_bps = if treasury.is_some() {
+            treasury_fee_bps
+        } else {
+            0
+        };
+        ctx.accounts.pool.treasury = treasury;
+        ctx.accounts.pool.bump = *ctx.bumps.get("pool").unwrap();
         Ok(())
     }
 
-    // RC6+ Pocket AMM
+    // Add Liquidity
+    // First deposit sets the price, after that deposits are matched to the pool ratio
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        max_amount_a: u64,
+        max_amount_b: u64,
+        min_lp_amount: u64,
+    ) -> Result<()> {
+        let reserve_a = ctx.accounts.vault_a.amount;
+        let reserve_b = ctx.accounts.vault_b.amount;
+        let lp_supply = ctx.accounts.lp_mint.supply;
+
+        // The first deposit locks MINIMUM_LIQUIDITY so the LP price can't be inflated by a
+        // single tiny share. It's priced off the vault balances, so tokens sent to the vaults
+        // before there were any LPs go to the first LP instead of skewing the price
+        let mut locked_lp_amount = 0;
+        let (amount_a, amount_b, lp_amount) = if lp_supply == 0 {
+            let liquidity = initial_liquidity(
+                reserve_a as u128 + max_amount_a as u128,
+                reserve_b as u128 + max_amount_b as u128,
+            )
+            .ok_or(PocketErrors::Overflow)?;
+            if liquidity <= MINIMUM_LIQUIDITY {
+                return err!(PocketErrors::InsufficientInitialLiquidity);
+            }
+            locked_lp_amount = MINIMUM_LIQUIDITY;
+            (max_amount_a, max_amount_b, liquidity - MINIMUM_LIQUIDITY)
+        } else {
+            let (amount_a, amount_b) = match mul_div(max_amount_a, reserve_b, reserve_a) {
+                Some(amount_b) if amount_b <= max_amount_b => (max_amount_a, amount_b),
+                _ => (
+                    mul_div(max_amount_b, reserve_a, reserve_b)
+                        .ok_or(PocketErrors::InsufficientLiquidity)?,
+                    max_amount_b,
+                ),
+            };
+            let lp_amount = std::cmp::min(
+                mul_div(amount_a, lp_supply, reserve_a).ok_or(PocketErrors::Overflow)?,
+                mul_div(amount_b, lp_supply, reserve_b).ok_or(PocketErrors::Overflow)?,
+            );
+            (amount_a, amount_b, lp_amount)
+        };
+
+        if lp_amount == 0 || lp_amount < min_lp_amount {
+            return err!(PocketErrors::SlippageExceeded);
+        }
+
+        for (from, to, amount) in [
+            (&ctx.accounts.user_a, &ctx.accounts.vault_a, amount_a),
+            (&ctx.accounts.user_b, &ctx.accounts.vault_b, amount_b),
+        ] {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: from.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: ctx.accounts.wallet.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
+
+        let mint_a = ctx.accounts.pool.mint_a;
+        let mint_b = ctx.accounts.pool.mint_b;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEEDS_POOL,
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &[ctx.accounts.pool.bump],
+        ]];
+        if locked_lp_amount > 0 {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::MintTo {
+                        mint: ctx.accounts.lp_mint.to_account_info(),
+                        to: ctx.accounts.lp_vault.to_account_info(),
+                        authority: ctx.accounts.pool.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                locked_lp_amount,
+            )?;
+        }
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.user_lp.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            lp_amount,
+        )
+    }
+
+    // Remove Liquidity
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+    ) -> Result<()> {
+        let lp_supply = ctx.accounts.lp_mint.supply;
+        let amount_a = mul_div(lp_amount, ctx.accounts.vault_a.amount, lp_supply)
+            .ok_or(PocketErrors::InsufficientLiquidity)?;
+        let amount_b = mul_div(lp_amount, ctx.accounts.vault_b.amount, lp_supply)
+            .ok_or(PocketErrors::InsufficientLiquidity)?;
+        if amount_a < min_amount_a || amount_b < min_amount_b {
+            return err!(PocketErrors::SlippageExceeded);
+        }
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.user_lp.to_account_info(),
+                    authority: ctx.accounts.wallet.to_account_info(),
+                },
+            ),
+            lp_amount,
+        )?;
+
+        let mint_a = ctx.accounts.pool.mint_a;
+        let mint_b = ctx.accounts.pool.mint_b;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEEDS_POOL,
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &[ctx.accounts.pool.bump],
+        ]];
+        for (from, to, amount) in [
+            (&ctx.accounts.vault_a, &ctx.accounts.user_a, amount_a),
+            (&ctx.accounts.vault_b, &ctx.accounts.user_b, amount_b),
+        ] {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: from.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: ctx.accounts.pool.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    // Swap
+    // a_to_b sells mint_a for mint_b, fails if less than min_amount_out comes back
+    pub fn swap(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        a_to_b: bool,
+    ) -> Result<()> {
+        let (user_in, user_out, vault_in, vault_out) = if a_to_b {
+            (
+                &ctx.accounts.user_a,
+                &ctx.accounts.user_b,
+                &ctx.accounts.vault_a,
+                &ctx.accounts.vault_b,
+            )
+        } else {
+            (
+                &ctx.accounts.user_b,
+                &ctx.accounts.user_a,
+                &ctx.accounts.vault_b,
+                &ctx.accounts.vault_a,
+            )
+        };
+
+        // Treasury cut comes off the top, the pool fee is priced into the swap
+        let treasury_fee = mul_div(
+            amount_in,
+            ctx.accounts.pool.treasury_fee_bps as u64,
+            BPS_DENOMINATOR,
+        )
+        .ok_or(PocketErrors::Overflow)?;
+        let pool_amount_in = amount_in - treasury_fee;
+        let amount_out = swap_amount_out(
+            pool_amount_in,
+            vault_in.amount,
+            vault_out.amount,
+            ctx.accounts.pool.fee_bps,
+        )
+        .ok_or(PocketErrors::Overflow)?;
+
+        if amount_out == 0 || amount_out >= vault_out.amount {
+            return err!(PocketErrors::InsufficientLiquidity);
+        }
+        if amount_out < min_amount_out {
+            return err!(PocketErrors::SlippageExceeded);
+        }
+
+        if treasury_fee > 0 {
+            let treasury_token_account = match &ctx.accounts.treasury_token_account {
+                Some(account)
+                    if Some(account.owner) == ctx.accounts.pool.treasury
+                        && account.mint == user_in.mint =>
+                {
+                    account
+                }
+                _ => return err!(PocketErrors::InvalidTreasuryAccount),
+            };
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: user_in.to_account_info(),
+                        to: treasury_token_account.to_account_info(),
+                        authority: ctx.accounts.wallet.to_account_info(),
+                    },
+                ),
+                treasury_fee,
+            )?;
+        }
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: user_in.to_account_info(),
+                    to: vault_in.to_account_info(),
+                    authority: ctx.accounts.wallet.to_account_info(),
+                },
+            ),
+            pool_amount_in,
+        )?;
+
+        let mint_a = ctx.accounts.pool.mint_a;
+        let mint_b = ctx.accounts.pool.mint_b;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEEDS_POOL,
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+            &[ctx.accounts.pool.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: vault_out.to_account_info(),
+                    to: user_out.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_out,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::tests::{faction, proposal};
+    use anchor_lang::solana_program::entrypoint::ProgramResult;
+    use anchor_lang::InstructionData;
+
+    // Backing storage for an AccountInfo handed to entry
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        is_signer: bool,
+        is_writable: bool,
+        executable: bool,
+    }
+
+    impl TestAccount {
+        fn wallet(key: Pubkey) -> TestAccount {
+            TestAccount {
+                key,
+                owner: system_program::ID,
+                lamports: 1_000_000_000,
+                data: vec![],
+                is_signer: true,
+                is_writable: true,
+                executable: false,
+            }
+        }
+
+        fn program(key: Pubkey) -> TestAccount {
+            TestAccount {
+                key,
+                owner: Pubkey::default(),
+                lamports: 1,
+                data: vec![],
+                is_signer: false,
+                is_writable: false,
+                executable: true,
+            }
+        }
+
+        fn state<T: AccountSerialize>(key: Pubkey, account: &T, space: usize) -> TestAccount {
+            let mut data = vec![];
+            account.try_serialize(&mut data).unwrap();
+            data.resize(space, 0);
+            TestAccount {
+                key,
+                owner: ID,
+                lamports: 1_000_000_000,
+                data,
+                is_signer: false,
+                is_writable: true,
+                executable: false,
+            }
+        }
+
+        fn info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                self.is_signer,
+                self.is_writable,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                self.executable,
+                0,
+            )
+        }
+
+        fn load<T: AccountDeserialize>(&self) -> T {
+            T::try_deserialize(&mut self.data.as_slice()).unwrap()
+        }
+    }
+
+    fn process(accounts: &mut [TestAccount], data: Vec<u8>) -> ProgramResult {
+        let infos: Vec<AccountInfo> = accounts.iter_mut().map(|a| a.info()).collect();
+        entry(&ID, &infos, &data)
+    }
+
+    fn custom<T: Into<u32>>(code: T) -> ProgramResult {
+        Err(ProgramError::Custom(code.into()))
+    }
+
+    // [server, system_program, config, proposal, faction] for update_proposal
+    fn update_proposal_accounts() -> Vec<TestAccount> {
+        let server = Pubkey::new_unique();
+        let (config_key, config_bump) = Pubkey::find_program_address(&[SEEDS_CONFIG], &ID);
+        let config = ProgramConfig {
+            authority: server,
+            pending_authority: None,
+            bump: config_bump,
+        };
+
+        let mut faction = faction();
+        let (faction_key, faction_bump) =
+            Pubkey::find_program_address(&[SEEDS_FACTION, faction.id.as_bytes()], &ID);
+        faction.bump = faction_bump;
+
+        let mut proposal = proposal(faction_key);
+        let (proposal_key, proposal_bump) =
+            Pubkey::find_program_address(&[SEEDS_PROPOSAL, proposal.id.as_bytes()], &ID);
+        proposal.bump = proposal_bump;
+
+        vec![
+            TestAccount::wallet(server),
+            TestAccount::program(system_program::ID),
+            TestAccount::state(config_key, &config, 8 + ProgramConfig::get_max_size()),
+            TestAccount::state(proposal_key, &proposal, 8 + Proposal::get_max_size()),
+            TestAccount::state(faction_key, &faction, 8 + Faction::get_max_size()),
+        ]
+    }
+
+    #[test]
+    fn update_proposal_opens_and_cancels() {
+        let mut accounts = update_proposal_accounts();
+        let open = instruction::UpdateProposal {
+            new_status: ProposalStatus::VOTING,
+        };
+        process(&mut accounts, open.data()).unwrap();
+        assert!(accounts[3].load::<Proposal>().status == ProposalStatus::VOTING);
+        assert_eq!(accounts[4].load::<Faction>().open_proposal_count, 1);
+
+        let cancel = instruction::UpdateProposal {
+            new_status: ProposalStatus::CANCELLED,
+        };
+        process(&mut accounts, cancel.data()).unwrap();
+        assert!(accounts[3].load::<Proposal>().status == ProposalStatus::CANCELLED);
+        assert_eq!(accounts[4].load::<Faction>().open_proposal_count, 0);
+    }
+
+    #[test]
+    fn update_proposal_leaves_results_to_finalize() {
+        let mut accounts = update_proposal_accounts();
+        for new_status in [ProposalStatus::PASSED, ProposalStatus::EXECUTED] {
+            let update = instruction::UpdateProposal { new_status };
+            assert_eq!(
+                process(&mut accounts, update.data()),
+                custom(PocketErrors::IllegalTransition)
+            );
+        }
+        assert!(accounts[3].load::<Proposal>().status == ProposalStatus::DRAFT);
+    }
+
+    #[test]
+    fn update_proposal_is_server_only() {
+        let mut accounts = update_proposal_accounts();
+        accounts[0].key = Pubkey::new_unique();
+        let open = instruction::UpdateProposal {
+            new_status: ProposalStatus::VOTING,
+        };
+        assert_eq!(
+            process(&mut accounts, open.data()),
+            custom(ErrorCode::ConstraintAddress)
+        );
+    }
 }
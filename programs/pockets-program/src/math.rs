@@ -0,0 +1,206 @@
+use crate::constant::*;
+use crate::error::*;
+use anchor_lang::prelude::*;
+
+// Checked voting power math, so bad bookkeeping surfaces as a decodable error instead of a panic
+pub fn safe_add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or(error!(PocketErrors::Overflow))
+}
+
+pub fn safe_sub(a: u64, b: u64) -> Result<u64> {
+    a.checked_sub(b).ok_or(error!(PocketErrors::Underflow))
+}
+
+// Integer square root, rounded down
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // n / 2 + 1 is never below the root and keeps x + n / x from overflowing
+    let mut x = n / 2 + 1;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+// LP minted for a pool's first deposit, the geometric mean of everything in the vaults
+pub fn initial_liquidity(balance_a: u128, balance_b: u128) -> Option<u64> {
+    u64::try_from(isqrt(balance_a.checked_mul(balance_b)?)).ok()
+}
+
+// Constant product output for a swap, fee_bps is taken off the input before pricing
+pub fn swap_amount_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_bps: u16,
+) -> Option<u64> {
+    let amount_in_with_fee =
+        (amount_in as u128).checked_mul((BPS_DENOMINATOR - fee_bps as u64) as u128)?;
+    let numerator = amount_in_with_fee.checked_mul(reserve_out as u128)?;
+    let denominator = (reserve_in as u128)
+        .checked_mul(BPS_DENOMINATOR as u128)?
+        .checked_add(amount_in_with_fee)?;
+    u64::try_from(numerator.checked_div(denominator)?).ok()
+}
+
+// amount * numerator / denominator without overflowing the intermediate product
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    u64::try_from((amount as u128).checked_mul(numerator as u128)? / denominator as u128).ok()
+}
+
+// mul_div rounded up
+pub fn mul_div_ceil(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    let product = (amount as u128).checked_mul(numerator as u128)?;
+    let denominator = denominator as u128;
+    u64::try_from(product / denominator + u128::from(product % denominator != 0)).ok()
+}
+
+// 2^(-elapsed / half_life) in DECAY_SCALE fixed point
+pub fn decay_factor(elapsed: u64, half_life: u64) -> u64 {
+    if half_life == 0 {
+        return 0;
+    }
+    let halvings = elapsed / half_life;
+    if halvings >= 64 {
+        return 0;
+    }
+
+    // Whole half-lives are a shift, the remainder is e^(-x * ln 2) by Taylor series
+    let scale = DECAY_SCALE as i128;
+    let x = ((elapsed % half_life) as i128 * scale / half_life as i128) * 693_147_181 / scale;
+    let mut term = scale;
+    let mut sum = scale;
+    for k in 1..=8 {
+        term = -term * x / scale / k;
+        sum += term;
+    }
+    (sum.max(0) as u64) >> halvings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: u64, expected: u64, tolerance: u64) {
+        assert!(
+            actual.abs_diff(expected) <= tolerance,
+            "{} not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn decay_factor_whole_half_lives() {
+        assert_eq!(decay_factor(0, 100), DECAY_SCALE);
+        assert_eq!(decay_factor(100, 100), DECAY_SCALE / 2);
+        assert_eq!(decay_factor(300, 100), DECAY_SCALE / 8);
+        assert_eq!(decay_factor(1000, 100), DECAY_SCALE >> 10);
+    }
+
+    #[test]
+    fn decay_factor_fractional_half_lives() {
+        // 2^-0.5 and 2^-1.5, the series is good to well under 1e-6
+        assert_close(decay_factor(50, 100), 707_106_781, 1_000);
+        assert_close(decay_factor(150, 100), 353_553_390, 1_000);
+        assert_close(decay_factor(1, 3), 793_700_526, 1_000);
+        assert_close(decay_factor(99, 100), 503_477_775, 1_000);
+    }
+
+    #[test]
+    fn decay_factor_is_non_increasing() {
+        let mut last = decay_factor(0, 7);
+        for elapsed in 1..200 {
+            let next = decay_factor(elapsed, 7);
+            assert!(next <= last, "rose at {}", elapsed);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn decay_factor_zero_half_life() {
+        assert_eq!(decay_factor(0, 0), 0);
+        assert_eq!(decay_factor(100, 0), 0);
+    }
+
+    #[test]
+    fn decay_factor_past_64_halvings() {
+        assert_eq!(decay_factor(64 * 100, 100), 0);
+        assert_eq!(decay_factor(u64::MAX, 1), 0);
+        assert_eq!(decay_factor(63 * 100, 100), DECAY_SCALE >> 63);
+    }
+
+    #[test]
+    fn isqrt_rounds_down() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        for n in 0..10_000u128 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n);
+        }
+    }
+
+    #[test]
+    fn initial_liquidity_is_the_geometric_mean() {
+        assert_eq!(initial_liquidity(4_000, 1_000), Some(2_000));
+        assert_eq!(initial_liquidity(10, 11), Some(10));
+        assert_eq!(
+            initial_liquidity(u64::MAX as u128, u64::MAX as u128),
+            Some(u64::MAX)
+        );
+        // Past u64 LP or a u128 product
+        assert_eq!(
+            initial_liquidity(u64::MAX as u128 + 2, u64::MAX as u128 + 2),
+            None
+        );
+        assert_eq!(initial_liquidity(u128::MAX, 2), None);
+    }
+
+    #[test]
+    fn swap_amount_out_rounds_down() {
+        // 100 * 1000 / 1100 = 90.9
+        assert_eq!(swap_amount_out(100, 1000, 1000, 0), Some(90));
+        // 997000 * 1000 / 10997000 = 90.66
+        assert_eq!(swap_amount_out(100, 1000, 1000, 30), Some(90));
+        assert_eq!(swap_amount_out(1, 1000, 1000, 0), Some(0));
+    }
+
+    #[test]
+    fn swap_amount_out_never_drains_the_pool() {
+        assert_eq!(swap_amount_out(u64::MAX, 1, 1000, 0), Some(999));
+        assert_eq!(swap_amount_out(0, 0, 1000, 0), None);
+    }
+
+    #[test]
+    fn mul_div_rounding() {
+        assert_eq!(mul_div(10, 1, 3), Some(3));
+        assert_eq!(mul_div_ceil(10, 1, 3), Some(4));
+        assert_eq!(mul_div(9, 1, 3), Some(3));
+        assert_eq!(mul_div_ceil(9, 1, 3), Some(3));
+        assert_eq!(mul_div_ceil(0, 5, 3), Some(0));
+    }
+
+    #[test]
+    fn mul_div_bad_inputs() {
+        assert_eq!(mul_div(1, 1, 0), None);
+        assert_eq!(mul_div_ceil(1, 1, 0), None);
+        assert_eq!(mul_div(u64::MAX, 2, 1), None);
+        assert_eq!(mul_div_ceil(u64::MAX, 2, 1), None);
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+    }
+}
@@ -1,16 +1,213 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { assert } from "chai";
 import { PocketsProgram } from "../target/types/pockets_program";
 
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
 describe("pockets-program", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.PocketsProgram as Program<PocketsProgram>;
+  const token = anchor.Spl.token(provider);
+  const server = provider.wallet.publicKey;
+
+  const pda = (...seeds: (Buffer | Uint8Array)[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const chainTime = async () => {
+    const slot = await provider.connection.getSlot();
+    return (await provider.connection.getBlockTime(slot)) as number;
+  };
+
+  const waitUntil = async (ts: number) => {
+    while ((await chainTime()) <= ts) {
+      await sleep(500);
+    }
+  };
+
+  const config = pda(Buffer.from("config"));
+
+  it("Initializes the config", async () => {
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    await program.methods
+      .initializeConfig(server)
+      .accounts({
+        upgradeAuthority: server,
+        systemProgram: SystemProgram.programId,
+        config,
+        program: program.programId,
+        programData,
+      })
+      .rpc();
+  });
+
+  it("Executes a passed treasury withdrawal proposal", async () => {
+    // Citizen NFT held by the server wallet
+    const mint = Keypair.generate();
+    const walletAta = Keypair.generate();
+    await token.methods
+      .initializeMint(0, server, null)
+      .accounts({ mint: mint.publicKey, rent: anchor.web3.SYSVAR_RENT_PUBKEY })
+      .preInstructions([await token.account.mint.createInstruction(mint)])
+      .signers([mint])
+      .rpc();
+    await token.methods
+      .initializeAccount()
+      .accounts({
+        account: walletAta.publicKey,
+        mint: mint.publicKey,
+        authority: server,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .preInstructions([await token.account.token.createInstruction(walletAta)])
+      .signers([walletAta])
+      .rpc();
+    await token.methods
+      .mintTo(new anchor.BN(1))
+      .accounts({ mint: mint.publicKey, to: walletAta.publicKey, authority: server })
+      .rpc();
+
+    const citizen = pda(Buffer.from("citizen"), mint.publicKey.toBuffer());
+    const citizenCheckpoints = pda(Buffer.from("checkpoints"), citizen.toBuffer());
+    await program.methods
+      .createCitizen()
+      .accounts({
+        server,
+        systemProgram: SystemProgram.programId,
+        config,
+        citizen,
+        mint: mint.publicKey,
+        citizenCheckpoints,
+      })
+      .rpc();
+
+    const factionId = "withdraw-test";
+    const faction = pda(Buffer.from("faction"), Buffer.from(factionId));
+    const treasury = pda(Buffer.from("faction"), faction.toBuffer());
+    await program.methods
+      .createFaction(factionId, new anchor.BN(100), new anchor.BN(50))
+      .accounts({
+        server,
+        systemProgram: SystemProgram.programId,
+        config,
+        faction,
+        firstCitizen: citizen,
+        firstCitizenCheckpoints: citizenCheckpoints,
+      })
+      .rpc();
+
+    await program.methods
+      .depositToTreasury(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        wallet: server,
+        walletAta: walletAta.publicKey,
+        systemProgram: SystemProgram.programId,
+        citizen,
+        faction,
+        treasury,
+      })
+      .rpc();
+
+    const proposalId = "withdraw-test";
+    const proposal = pda(Buffer.from("proposal"), Buffer.from(proposalId));
+    const proposalInstruction = pda(Buffer.from("proposal_ix"), proposal.toBuffer());
+    const startTs = (await chainTime()) + 5;
+    const endTs = startTs + 5;
+    await program.methods
+      .createProposal(
+        proposalId,
+        null,
+        new anchor.BN(startTs),
+        new anchor.BN(endTs),
+        { title: "Pay the recipient", uri: "", contentHash: new Array(32).fill(0) },
+        { treasury: {} }
+      )
+      .accounts({
+        server,
+        systemProgram: SystemProgram.programId,
+        config,
+        proposal,
+        faction,
+        proposer: citizen,
+      })
+      .rpc();
+
+    // withdraw_from_treasury, signed by the Faction PDA when the proposal is executed
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(LAMPORTS_PER_SOL / 2);
+    await program.methods
+      .createProposalInstructions(new anchor.BN(0), [
+        {
+          programId: program.programId,
+          accounts: [
+            { pubkey: faction, isSigner: true, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: treasury, isSigner: false, isWritable: true },
+            { pubkey: recipient, isSigner: false, isWritable: true },
+          ],
+          data: program.coder.instruction.encode("withdrawFromTreasury", { amount }),
+        },
+      ])
+      .accounts({
+        server,
+        systemProgram: SystemProgram.programId,
+        config,
+        proposalInstruction,
+        proposal,
+      })
+      .rpc();
+
+    await program.methods
+      .updateProposal({ voting: {} })
+      .accounts({ server, systemProgram: SystemProgram.programId, config, proposal, faction })
+      .rpc();
+
+    await waitUntil(startTs);
+    const vote = pda(Buffer.from("vote"), citizen.toBuffer(), proposal.toBuffer());
+    await program.methods
+      .voteOnProposal(new anchor.BN(100), 0)
+      .accounts({
+        wallet: server,
+        walletAta: walletAta.publicKey,
+        systemProgram: SystemProgram.programId,
+        citizen,
+        vote,
+        proposal,
+        faction,
+        citizenCheckpoints,
+      })
+      .rpc();
+
+    await waitUntil(endTs);
+    await program.methods.finalizeProposal().accounts({ proposal, faction }).rpc();
+    assert.deepEqual((await program.account.proposal.fetch(proposal)).status, { passed: {} });
+
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    await program.methods
+      .executeProposal()
+      .accounts({ proposal, proposalInstruction, faction })
+      .remainingAccounts([
+        { pubkey: program.programId, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: treasury, isSigner: false, isWritable: true },
+        { pubkey: recipient, isSigner: false, isWritable: true },
+      ])
+      .rpc();
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    //const tx = await program.methods.initialize().rpc();
-    //console.log("Your transaction signature", tx);
+    assert.equal(await provider.connection.getBalance(recipient), amount.toNumber());
+    assert.equal(
+      await provider.connection.getBalance(treasury),
+      treasuryBefore - amount.toNumber()
+    );
+    assert.deepEqual((await program.account.proposal.fetch(proposal)).status, { executed: {} });
+    assert.isTrue((await program.account.proposalInstruction.fetch(proposalInstruction)).executed);
   });
 });